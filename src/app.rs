@@ -2,10 +2,25 @@ use iced::{Application, Command};
 use iced::executor::Default as DefaultExecutor;
 use iced::Theme as IcedTheme;
 
+use crate::controller::clipboard::ClipboardAction;
+use crate::controller::history::HistoryConfig;
+use crate::controller::journal::{JournalEntry, JournalWriter};
 use crate::controller::Controller;
 use crate::message::Message;
+use crate::model::catalog::Catalog;
 use crate::view;
 
+#[derive(Debug, Default)]
+pub struct Flags {
+    pub history_config: HistoryConfig,
+    pub catalog: Catalog,
+    /// Messages replayed before the session starts
+    pub replay: Vec<JournalEntry>,
+    /// Journal recording the messages of the session, the replayed
+    /// messages are not recorded
+    pub journal: Option<JournalWriter>,
+}
+
 #[derive(Debug, Default)]
 pub struct App {
     controller: Controller
@@ -18,10 +33,17 @@ impl Application for App {
 
     type Theme = IcedTheme;
 
-    type Flags = ();
+    type Flags = Flags;
 
-    fn new(_: Self::Flags) -> (Self, Command<Self::Message>) {
-        (Self::default(), Command::none())
+    fn new(flags: Self::Flags) -> (Self, Command<Self::Message>) {
+        let mut controller = Controller::new(flags.history_config);
+        controller.set_catalog(flags.catalog);
+        controller.replay(flags.replay);
+        if let Some(journal) = flags.journal {
+            controller.record(journal);
+        }
+
+        (Self { controller }, Command::none())
     }
 
     fn title(&self) -> String {
@@ -31,7 +53,15 @@ impl Application for App {
     fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
         self.controller.update(message);
 
-        Command::none()
+        if let Some(action) = self.controller.take_file_action() {
+            self.controller.run_file_action(action);
+        }
+
+        match self.controller.take_clipboard_action() {
+            Some(ClipboardAction::Write(content)) => iced::clipboard::write(content),
+            Some(ClipboardAction::Read) => iced::clipboard::read(Message::Paste),
+            None => Command::none(),
+        }
     }
 
     fn view(&self) -> iced::Element<'_, Self::Message, iced::Renderer<Self::Theme>> {
@@ -1,35 +1,205 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
 use crate::message::Message;
-use crate::model::plan::Plan;
+use crate::model::access::{Accessibility, Agent};
+use crate::model::block::Block;
+use crate::model::catalog::Catalog;
+use crate::model::coverage::{self, Coverage};
+use crate::model::egress::Egress;
+use crate::model::motion::{self, PathError};
+use crate::model::optimize::{self, CategoryClusters, MinSpacing, NearWalls};
+use crate::model::packing;
+use crate::model::plan::{BlockId, Plan, ShapeId};
+use crate::model::report::Report;
+use crate::model::room::Room;
+use crate::model::shape::{self, Flip, Shape};
+use crate::model::vec2::Vec2;
 
+use self::analysis::Analysis;
+use self::clipboard::{ClipboardAction, ClipboardItem, Payload};
 use self::command::BoxedCommand;
-use self::state::{ControllerState, ShapeType};
+use self::document::{DocumentError, FileAction};
+use self::history::{History, HistoryConfig, HistoryEntry};
+use self::journal::{JournalEntry, JournalWriter};
+use self::state::{
+    AccessDraft, ArrayDraft, AreaAction, BlockDraft, ControllerState, OptimizationPreview,
+    OptimizerDraft, PackingDraft, ShapeType,
+};
 
+pub mod analysis;
+pub mod clipboard;
 pub mod command;
+pub mod document;
+pub mod history;
+pub mod journal;
 pub mod state;
 
+/// Offset of the duplicated shapes from the original ones, and of the shapes
+/// pasted while the cursor is outside of the plan from the copied ones
+const DUPLICATE_OFFSET: Vec2 = Vec2::new(1, 1);
+
+/// Longest walking distance to an exit allowed by default, about 30 m
+const DEFAULT_EGRESS_LIMIT: &str = "150";
+
 #[derive(Debug)]
 pub struct Controller {
     plan: Plan,
-    done_commands: Vec<BoxedCommand>,
-    undone_commands: Vec<BoxedCommand>,
+    /// Counter of the changes of the plan, the analyses are computed again
+    /// when it changes
+    revision: u64,
+    history: History,
     state: ControllerState,
     scale: f32,
+    cursor: Option<Vec2>,
+    gesture_in_progress: bool,
+    file_path: String,
+    /// Outcome of the last file operation, shown to the user
+    notice: Option<String>,
+    /// Start of the session clock, which timestamps the commands
+    /// and the journal entries
+    start: Instant,
+    /// Time of the message being handled in the session clock
+    time: Duration,
+    journal: Option<JournalWriter>,
+    catalog: Catalog,
+    /// Block whose definition is being edited
+    block_draft: Option<BlockDraft>,
+    selection: Vec<ShapeId>,
+    /// Array of the selection being prepared
+    array_draft: Option<ArrayDraft>,
+    packing_draft: PackingDraft,
+    optimizer_draft: Option<OptimizerDraft>,
+    optimization: Option<OptimizationPreview>,
+    /// Waypoints of the last path found for the selected shape
+    motion_path: Option<Vec<Shape>>,
+    access_draft: AccessDraft,
+    entrance: Option<Vec2>,
+    /// Accessibility of the plan for an agent from the entrance, kept up
+    /// to date while it is shown
+    accessibility: Analysis<(Agent, Vec2), Accessibility>,
+    /// Longest walking distance to an exit, as typed by the user
+    egress_limit: String,
+    /// Walking distances to the exits within a maximum distance, kept up
+    /// to date while they are shown
+    egress: Analysis<f32, Egress>,
+    /// Category of the circles of the coverage analysis
+    coverage_category: Option<String>,
+    coverage_obstacles: bool,
+    /// Coverage of the floor by a category with or without obstacles, kept
+    /// up to date while it is shown
+    coverage: Analysis<(String, bool), Coverage>,
+    /// Occupancy report of the plan, kept up to date while it is shown
+    report: Analysis<(), Report>,
+    show_report: bool,
+    /// Access to the system clipboard requested by the last message
+    clipboard_action: Option<ClipboardAction>,
+    /// Access to the files requested by the last message
+    file_action: Option<FileAction>,
 }
 
 impl Default for Controller {
     fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl Controller {
+    pub fn new(history_config: HistoryConfig) -> Self {
         Self {
             plan: Default::default(),
-            done_commands: Default::default(),
-            undone_commands: Default::default(),
+            revision: 0,
+            history: History::new(history_config),
             state: Default::default(),
             scale: 20.0,
+            cursor: None,
+            gesture_in_progress: false,
+            file_path: "plan.placo".into(),
+            notice: None,
+            start: Instant::now(),
+            time: Duration::ZERO,
+            journal: None,
+            catalog: Default::default(),
+            block_draft: None,
+            selection: Vec::new(),
+            array_draft: None,
+            packing_draft: Default::default(),
+            optimizer_draft: None,
+            optimization: None,
+            motion_path: None,
+            access_draft: Default::default(),
+            entrance: None,
+            accessibility: Default::default(),
+            egress_limit: DEFAULT_EGRESS_LIMIT.into(),
+            egress: Default::default(),
+            coverage_category: None,
+            coverage_obstacles: false,
+            coverage: Default::default(),
+            report: Default::default(),
+            show_report: false,
+            clipboard_action: None,
+            file_action: None,
         }
     }
-}
 
-impl Controller {
+    /// Feed the messages of a journal to the controller, at the time they
+    /// were recorded. Replayed on a fresh controller with the same catalog,
+    /// they reproduce the plan of the recorded session.
+    ///
+    /// The session clock then resumes from the time of the last message, so
+    /// the next messages are neither merged with the replayed commands nor
+    /// recorded back in time. The clipboard and file accesses they request
+    /// are dropped, so the files opened during the session aren't read again
+    pub fn replay(&mut self, entries: impl IntoIterator<Item = JournalEntry>) {
+        let mut last_time = None;
+        for JournalEntry { time, message } in entries {
+            self.update_at(time, message);
+            last_time = Some(time);
+        }
+
+        if let Some(start) = last_time.and_then(|time| Instant::now().checked_sub(time)) {
+            self.start = start;
+        }
+        // The replayed messages don't reach the clipboard nor the files again
+        self.clipboard_action = None;
+        self.file_action = None;
+    }
+
+    pub fn set_catalog(&mut self, catalog: Catalog) {
+        self.catalog = catalog;
+    }
+
+    /// Record every message received from now on in the `journal`
+    pub fn record(&mut self, journal: JournalWriter) {
+        self.journal = Some(journal);
+    }
+
     pub fn update(&mut self, message: Message) {
+        self.update_at(self.start.elapsed(), message);
+    }
+
+    /// Handle a `message` received at `time` in the session clock
+    pub fn update_at(&mut self, time: Duration, message: Message) {
+        self.time = time;
+
+        let cursor_moved = matches!(message, Message::CursorMoved(_));
+        if !cursor_moved {
+            self.notice = None;
+        }
+
+        if let Some(journal) = &mut self.journal {
+            let entry = JournalEntry {
+                time,
+                message: message.clone(),
+            };
+            if let Err(err) = journal.record(&entry) {
+                self.notice =
+                    Some(format!("Failed to record the journal, recording stopped: {err}"));
+                self.journal = None;
+            }
+        }
+
         match (&self.state, message) {
             (ControllerState::Idle, Message::AddRectangleButton) => {
                 self.state = ControllerState::AddingShape(ShapeType::Rectangle)
@@ -40,59 +210,982 @@ impl Controller {
             (ControllerState::Idle, Message::MoveButton) => {
                 self.state = ControllerState::MovingShapes
             }
-            (ControllerState::Idle, Message::Undo) => self.undo(),
-            (ControllerState::Idle, Message::Redo) => self.redo(),
+            (ControllerState::Idle, Message::MeasureButton(kind)) => {
+                self.state = ControllerState::Measuring(kind)
+            }
+            (ControllerState::Idle, Message::DimensionButton) => {
+                self.state = ControllerState::Annotating
+            }
+            (ControllerState::Idle, Message::CatalogButton(index)) => {
+                if let Some(entry) = self.catalog.get(index) {
+                    self.state = ControllerState::PlacingItem(entry.clone())
+                }
+            }
+            // Undoing during a gesture only cancels the gesture, the
+            // other history operations cancel it before changing the plan
+            (_, Message::Undo) if self.gesture_in_progress => self.gesture_in_progress = false,
+            (_, Message::Undo) => self.undo(),
+            (_, Message::Redo) => {
+                self.gesture_in_progress = false;
+                self.redo()
+            }
+            (_, Message::JumpToHistory(position)) => {
+                self.gesture_in_progress = false;
+                self.history.jump_to(position, &mut self.plan);
+                self.revision += 1;
+            }
+            (_, Message::PreviousBranch) => {
+                self.gesture_in_progress = false;
+                self.history.switch_branch(false, &mut self.plan);
+                self.revision += 1;
+            }
+            (_, Message::NextBranch) => {
+                self.gesture_in_progress = false;
+                self.history.switch_branch(true, &mut self.plan);
+                self.revision += 1;
+            }
+            (_, Message::GestureStarted) => self.gesture_in_progress = true,
+            (_, Message::GestureEnded) => self.gesture_in_progress = false,
             (_, Message::ScaleUp) => self.scale += 5.0,
             (_, Message::ScaleDown) => {
                 self.scale -= 5.0;
                 self.scale = self.scale.max(5.0);
             },
-            (_, Message::Cancel) => self.state = ControllerState::Idle,
+            (_, Message::Cancel) => {
+                self.state = ControllerState::Idle;
+                self.gesture_in_progress = false;
+            }
+            (_, Message::CursorMoved(cursor)) => self.cursor = cursor,
+            (_, Message::FilePathChanged(path)) => self.file_path = path,
+            (ControllerState::Idle, Message::SaveButton) => {
+                self.file_action = Some(FileAction::Save(self.file_path.clone().into()))
+            }
+            (ControllerState::Idle, Message::OpenButton) => {
+                self.file_action = Some(FileAction::Open(self.file_path.clone().into()))
+            }
             (_, Message::AddShape(shape)) => {
                 let id = self.plan.generate_shape_id();
                 self.do_command(command::add_shape(id, shape));
                 self.state = ControllerState::Idle;
+                self.gesture_in_progress = false;
+            }
+            // Items are usually placed several times in a row,
+            // so the controller stays in the placing mode
+            (_, Message::AddItem(shape, metadata)) => {
+                let id = self.plan.generate_shape_id();
+                self.do_command(command::add_item(id, shape, metadata));
+            }
+            (ControllerState::Idle, Message::CreateBlockButton) => {
+                self.state = ControllerState::SelectingArea(AreaAction::CreateBlock)
+            }
+            (ControllerState::Idle, Message::GroupButton) => {
+                self.state = ControllerState::SelectingArea(AreaAction::Group)
+            }
+            (ControllerState::Idle, Message::UngroupButton) => {
+                self.state = ControllerState::SelectingArea(AreaAction::Ungroup)
+            }
+            (ControllerState::Idle, Message::SetRoomButton) => {
+                self.state = ControllerState::SelectingArea(AreaAction::SetRoom)
+            }
+            (_, Message::PackQuantityChanged(entry, value))
+                if entry < self.catalog.entries().len() =>
+            {
+                self.packing_draft.set_quantity(entry, value)
+            }
+            (ControllerState::Idle, Message::PackButton) => self.pack(),
+            (ControllerState::Idle, Message::MotionButton(rotation))
+                if self.has_single_selection() =>
+            {
+                self.motion_path = None;
+                self.state = ControllerState::PlanningMotion { rotation };
+            }
+            (ControllerState::PlanningMotion { rotation }, Message::PlanMotion(target)) => {
+                self.plan_motion(target, *rotation)
+            }
+            (ControllerState::Idle, Message::AccessButton) => {
+                self.state = ControllerState::AnalyzingAccess
+            }
+            (_, Message::AccessDraftChanged(field, value)) => {
+                self.access_draft.set_field(field, value)
+            }
+            (ControllerState::AnalyzingAccess, Message::SetEntrance(pos)) => {
+                self.entrance = Some(pos)
+            }
+            (ControllerState::Idle, Message::CoverageButton) => {
+                let categories = coverage::categories(&self.plan);
+                if !self
+                    .coverage_category
+                    .as_ref()
+                    .is_some_and(|category| categories.contains(category))
+                {
+                    self.coverage_category = categories.into_iter().next();
+                }
+                self.state = ControllerState::AnalyzingCoverage;
+            }
+            (_, Message::CoverageCategory(category)) => self.coverage_category = Some(category),
+            (_, Message::CoverageObstaclesToggled(obstacles)) => {
+                self.coverage_obstacles = obstacles
+            }
+            (_, Message::ReportButton) => self.show_report = !self.show_report,
+            (ControllerState::Idle, Message::ExportReportButton) => self.export_report(),
+            (ControllerState::Idle, Message::EgressButton) => {
+                self.state = ControllerState::AnalyzingEgress
+            }
+            (_, Message::EgressLimitChanged(value)) => self.egress_limit = value,
+            (ControllerState::AnalyzingEgress, Message::AddExit(pos)) => {
+                let old = self.plan.exits().to_vec();
+                let mut new = old.clone();
+                new.push(pos);
+                self.do_command(command::set_exits(old, new));
+            }
+            (ControllerState::AnalyzingEgress, Message::RemoveExit(index))
+                if index < self.plan.exits().len() =>
+            {
+                let old = self.plan.exits().to_vec();
+                let mut new = old.clone();
+                new.remove(index);
+                self.do_command(command::set_exits(old, new));
+            }
+            (ControllerState::Idle, Message::OptimizeButton) => {
+                self.optimizer_draft = Some(Default::default())
+            }
+            (_, Message::OptimizerDraftChanged(field, value)) => {
+                if let Some(draft) = &mut self.optimizer_draft {
+                    draft.set_field(field, value);
+                }
+            }
+            (ControllerState::Idle, Message::PreviewOptimization) => self.preview_optimization(),
+            (ControllerState::Idle, Message::ApplyOptimization) => {
+                if let Some(preview) = self.optimization_preview() {
+                    let shapes = preview.shapes.clone();
+                    let description = format!("Optimize layout of {} shapes", shapes.len());
+                    self.do_command(command::update_shapes(shapes, Some(description)));
+                    self.optimizer_draft = None;
+                    self.optimization = None;
+                }
+            }
+            (_, Message::CancelOptimization) => {
+                self.optimizer_draft = None;
+                self.optimization = None;
+            }
+            (ControllerState::Idle, Message::SelectButton) => {
+                self.state = ControllerState::SelectingArea(AreaAction::Select)
+            }
+            (ControllerState::SelectingArea(action), Message::AreaSelected(corner1, corner2)) => {
+                let shape_ids = self.area_selection(*action, corner1, corner2);
+                match action {
+                    AreaAction::CreateBlock => self.create_block(shape_ids),
+                    AreaAction::Group => self.group(shape_ids),
+                    AreaAction::Ungroup => self.ungroup(shape_ids),
+                    AreaAction::Select => self.selection = shape_ids,
+                    AreaAction::SetRoom => self.set_room(corner1, corner2),
+                }
+                self.state = ControllerState::Idle;
+                self.gesture_in_progress = false;
+            }
+            (ControllerState::Idle, Message::BlockButton(block_id))
+                if self.plan.get_block(block_id).is_some() =>
+            {
+                self.state = ControllerState::PlacingBlock(block_id)
+            }
+            (ControllerState::PlacingBlock(_), Message::AddInstance(block_id, pos)) => {
+                self.add_instance(block_id, pos)
+            }
+            (ControllerState::Idle, Message::EditBlockButton(block_id)) => {
+                self.block_draft = self
+                    .plan
+                    .get_block(block_id)
+                    .map(|block| BlockDraft::new(block_id, block));
+            }
+            (ControllerState::Idle, Message::BlockDraftChanged(shape, field, value)) => {
+                if let Some(draft) = &mut self.block_draft {
+                    draft.set_field(shape, field, value);
+                }
+            }
+            (ControllerState::Idle, Message::ApplyBlockEdit) => self.apply_block_edit(),
+            (_, Message::CancelBlockEdit) => self.block_draft = None,
+            (ControllerState::Idle, Message::Select(shape_ids)) => self.selection = shape_ids,
+            (ControllerState::Idle, Message::CopyButton) => self.copy_selection(),
+            (ControllerState::Idle, Message::CutButton) => {
+                self.copy_selection();
+                self.delete_selection();
+            }
+            (ControllerState::Idle, Message::DeleteButton) => self.delete_selection(),
+            (ControllerState::Idle, Message::DuplicateButton) => {
+                if let Some(payload) = self.selection_payload() {
+                    self.paste(&payload, payload.origin() + DUPLICATE_OFFSET);
+                }
+            }
+            (ControllerState::Idle, Message::PasteButton) => {
+                self.clipboard_action = Some(ClipboardAction::Read)
+            }
+            (ControllerState::Idle, Message::Paste(text)) => {
+                match text.as_deref().and_then(Payload::from_text) {
+                    Some(payload) => {
+                        let target = self
+                            .cursor
+                            .unwrap_or(payload.origin() + DUPLICATE_OFFSET);
+                        self.paste(&payload, target)
+                    }
+                    None => self.notice = Some("The clipboard doesn't contain shapes".into()),
+                }
+            }
+            (ControllerState::Idle, Message::ArrayButton) if self.has_selection() => {
+                self.array_draft = Some(Default::default())
+            }
+            (_, Message::ArrayDraftChanged(field, value)) => {
+                if let Some(draft) = &mut self.array_draft {
+                    draft.set_field(field, value);
+                }
+            }
+            (ControllerState::Idle, Message::ApplyArray) => self.apply_array(),
+            (_, Message::CancelArray) => self.array_draft = None,
+            (ControllerState::Idle, Message::FlipButton(flip)) => {
+                let shapes: Vec<_> = self
+                    .selection()
+                    .into_iter()
+                    .filter_map(|shape_id| self.plan.get_shape(shape_id))
+                    .collect();
+                if let Some((top_left, bottom_right)) = shape::bounding_box(shapes) {
+                    let axis = match flip {
+                        Flip::Horizontal => top_left.x + bottom_right.x,
+                        Flip::Vertical => top_left.y + bottom_right.y,
+                    };
+                    self.mirror_selection(flip, axis);
+                }
+            }
+            (ControllerState::Idle, Message::MirrorButton(flip)) if self.has_selection() => {
+                self.state = ControllerState::Mirroring(flip)
+            }
+            (ControllerState::Mirroring(_), Message::Mirror(flip, axis)) => {
+                // Stay in the mode to try another axis when this one fails
+                let mirrored = self.mirror_selection(flip, 2 * axis);
+                if mirrored {
+                    self.state = ControllerState::Idle;
+                }
             }
             (_, Message::UpdateShape(shape_id, shape)) => {
                 if let Some(old) = self.plan.get_shape(shape_id) {
                     self.do_command(command::update_shape(shape_id, old.clone(), shape));
                 }
+                self.gesture_in_progress = false;
             },
+            (_, Message::UpdateShapes(shapes)) => {
+                let shapes: Vec<_> = shapes
+                    .into_iter()
+                    .filter_map(|(shape_id, shape)| {
+                        let old = self.plan.get_shape(shape_id)?.clone();
+                        Some((shape_id, old, shape))
+                    })
+                    .collect();
+                if !shapes.is_empty() {
+                    self.do_command(command::update_shapes(shapes, None));
+                }
+                self.gesture_in_progress = false;
+            }
+            (_, Message::AddDimension(dimension)) => {
+                let id = self.plan.generate_dimension_id();
+                self.do_command(command::add_dimension(id, dimension));
+            }
+            (_, Message::RemoveDimension(dimension_id)) => {
+                if let Some(dimension) = self.plan.get_dimension(dimension_id) {
+                    self.do_command(command::remove_dimension(dimension_id, dimension.clone()));
+                }
+            }
             _ => {}
         }
+
+        self.refresh_analyses();
     }
 
     pub fn plan(&self) -> &Plan {
         &self.plan
     }
 
+    /// Shapes of the area between the two corners used by the `action`
+    pub fn area_selection(&self, action: AreaAction, corner1: Vec2, corner2: Vec2) -> Vec<ShapeId> {
+        let shapes = self.plan.shapes_in_area(corner1, corner2).map(|(id, _)| *id);
+
+        match action {
+            AreaAction::SetRoom => Vec::new(),
+            AreaAction::CreateBlock => shapes
+                .filter(|id| self.plan.get_block_member(*id).is_none())
+                .collect(),
+            // Groups are selected as a whole, even when they are
+            // partially inside of the area
+            AreaAction::Group | AreaAction::Ungroup | AreaAction::Select => {
+                let mut selection = Vec::new();
+                for shape_id in shapes {
+                    if action == AreaAction::Ungroup && self.plan.get_group(shape_id).is_none() {
+                        continue;
+                    }
+                    for member in self.plan.group_of(shape_id) {
+                        if !selection.contains(&member) {
+                            selection.push(member);
+                        }
+                    }
+                }
+                selection
+            }
+        }
+    }
+
+    /// Make a block of the shapes, they become its first instance
+    fn create_block(&mut self, shape_ids: Vec<ShapeId>) {
+        let shapes: Vec<_> = shape_ids
+            .iter()
+            .filter_map(|id| self.plan.get_shape(*id).cloned())
+            .collect();
+
+        let name = format!("Block {}", self.plan.blocks().count() + 1);
+        let Some((_, block)) = Block::from_shapes(name, &shapes) else {
+            self.notice = Some("No shape outside of a block in this area".into());
+            return;
+        };
+
+        let block_id = self.plan.generate_block_id();
+        self.do_command(command::create_block(block_id, block, shape_ids));
+    }
+
+    fn set_room(&mut self, corner1: Vec2, corner2: Vec2) {
+        match Room::new(corner1, corner2) {
+            Some(room) => self.do_command(command::set_room(self.plan.room().copied(), Some(room))),
+            None => self.notice = Some("The room must have an area".into()),
+        }
+    }
+
+    /// Lay out the quantities of catalog items of the packing draft in the
+    /// room, around the shapes already there, and report how many fit
+    fn pack(&mut self) {
+        let Some(room) = self.plan.room().copied() else {
+            self.notice = Some("Set the room to pack the items in".into());
+            return;
+        };
+        let entries = self.catalog.entries();
+        let Some(counts) = self.packing_draft.counts(entries.len()) else {
+            self.notice = Some(format!(
+                "Quantities must be whole numbers, at most {} items in total",
+                PackingDraft::MAX_ITEMS
+            ));
+            return;
+        };
+
+        let (items, metadata): (Vec<_>, Vec<_>) = entries
+            .iter()
+            .zip(counts)
+            .flat_map(|(entry, count)| {
+                std::iter::repeat_n((entry.shape_at(room.top_left()), entry.metadata()), count)
+            })
+            .unzip();
+        if items.is_empty() {
+            self.notice = Some("Enter the quantities of catalog items to pack".into());
+            return;
+        }
+
+        let placed = packing::pack(&self.plan, &room, &items);
+        self.notice = Some(format!("{} of {} items fit in the room", placed.len(), items.len()));
+        if placed.is_empty() {
+            return;
+        }
+
+        let shapes = placed
+            .into_iter()
+            .map(|(index, shape)| {
+                (self.plan.generate_shape_id(), shape, Some(metadata[index].clone()))
+            })
+            .collect();
+        self.do_command(command::add_shapes(shapes));
+    }
+
+    /// Run the optimizer on the selected shapes, or on every shape when
+    /// nothing is selected, and keep its layout to be previewed
+    fn preview_optimization(&mut self) {
+        let Some((settings, [spacing, walls, clusters])) =
+            self.optimizer_draft.as_ref().and_then(OptimizerDraft::parse)
+        else {
+            self.notice = Some(format!(
+                "Seed and iterations must be whole numbers, at most {} iterations, \
+                 weights positive numbers",
+                OptimizerDraft::MAX_ITERATIONS
+            ));
+            return;
+        };
+
+        let mut shape_ids = self.selection();
+        if shape_ids.is_empty() {
+            shape_ids = self.plan.iter().map(|(id, _)| *id).collect();
+        }
+        let costs: Vec<(f64, &dyn optimize::Cost)> = [
+            (spacing, &MinSpacing as &dyn optimize::Cost),
+            (walls, &NearWalls),
+            (clusters, &CategoryClusters),
+        ]
+        .into_iter()
+        .filter(|(weight, _)| *weight > 0.0)
+        .collect();
+
+        let optimized = optimize::optimize(&self.plan, &shape_ids, &costs, settings);
+        let shapes = optimized
+            .shapes
+            .into_iter()
+            .filter_map(|(shape_id, new)| {
+                let old = self.plan.get_shape(shape_id)?;
+                (*old != new).then(|| (shape_id, old.clone(), new))
+            })
+            .collect::<Vec<_>>();
+        if shapes.is_empty() {
+            self.notice = Some("No better layout found".into());
+        }
+        self.optimization = Some(OptimizationPreview {
+            shapes,
+            initial_cost: optimized.initial_cost,
+            cost: optimized.cost,
+        });
+    }
+
+    fn plan_motion(&mut self, target: Vec2, rotation: bool) {
+        let [shape_id] = self.selection()[..] else {
+            return;
+        };
+
+        let path = motion::find_path(&self.plan, shape_id, target, rotation);
+        self.notice = Some(match &path {
+            Ok(path) => {
+                let length: f32 = path
+                    .windows(2)
+                    .map(|pair| motion::center(&pair[0]).exact_distance(&motion::center(&pair[1])))
+                    .sum();
+                format!("Path found with {} waypoints, {length:.1} long", path.len())
+            }
+            Err(PathError::Unreachable) => "No collision-free path to this spot".into(),
+            Err(PathError::LimitReached) => {
+                "The search gave up before finding a path to this spot".into()
+            }
+        });
+        self.motion_path = path.ok();
+    }
+
+    /// Keep the analyses shown up to date with the plan and their
+    /// parameters, and drop the hidden ones
+    fn refresh_analyses(&mut self) {
+        self.analyze_access();
+        self.analyze_egress();
+        self.analyze_coverage();
+        let shown = self.show_report.then_some(());
+        self.report.refresh(self.revision, shown, |_| Some(Report::new(&self.plan)));
+    }
+
+    fn analyze_access(&mut self) {
+        let shown = matches!(self.state, ControllerState::AnalyzingAccess);
+        let agent = self.access_draft.agent();
+        if shown && agent.is_none() {
+            self.notice = Some("The sizes of the agent must be positive numbers".into());
+        }
+
+        let params = agent.zip(self.entrance).filter(|_| shown);
+        let computed = self.accessibility.refresh(self.revision, params, |(agent, entrance)| {
+            Accessibility::new(&self.plan, *agent, *entrance)
+        });
+        if let Some(accessibility) = self.accessibility.get().filter(|_| computed) {
+            self.notice = Some(format!(
+                "{} unreachable shapes, {} bottlenecks",
+                accessibility.unreachable.len(),
+                accessibility.bottlenecks.len()
+            ));
+        }
+    }
+
+    fn analyze_egress(&mut self) {
+        let shown = matches!(self.state, ControllerState::AnalyzingEgress);
+        let max_distance = self
+            .egress_limit
+            .trim()
+            .parse()
+            .ok()
+            .filter(|distance: &f32| *distance > 0.0);
+        if shown && max_distance.is_none() {
+            self.notice = Some("The maximum distance must be a positive number".into());
+        }
+
+        let params = max_distance.filter(|_| shown);
+        let computed = self.egress.refresh(self.revision, params, |max_distance| {
+            Egress::new(&self.plan, *max_distance)
+        });
+        if let Some(egress) = self.egress.get().filter(|_| computed) {
+            let free = egress.points().count();
+            let too_far = egress.too_far().count();
+            self.notice = Some(format!(
+                "{:.0}% of the free space is farther than {} from an exit",
+                100.0 * too_far as f32 / free.max(1) as f32,
+                egress.max_distance()
+            ));
+        }
+    }
+
+    fn analyze_coverage(&mut self) {
+        let shown = matches!(self.state, ControllerState::AnalyzingCoverage);
+        if shown && self.coverage_category.is_none() {
+            self.notice = Some("No circle of the plan has a category".into());
+        }
+
+        let params = self
+            .coverage_category
+            .clone()
+            .map(|category| (category, self.coverage_obstacles))
+            .filter(|_| shown);
+        let computed = self.coverage.refresh(self.revision, params, |(category, obstacles)| {
+            Coverage::new(&self.plan, category, *obstacles)
+        });
+        if let Some(coverage) = self.coverage.get().filter(|_| computed) {
+            self.notice = Some(format!(
+                "{:.0}% covered, {:.0}% by several emitters",
+                100.0 * coverage.covered(),
+                100.0 * coverage.redundant()
+            ));
+        }
+    }
+
+    /// Request the occupancy report to be written as CSV next to the plan file
+    fn export_report(&mut self) {
+        let path = Path::new(&self.file_path).with_extension("csv");
+        self.file_action = Some(FileAction::ExportReport(path, Report::new(&self.plan).to_csv()));
+    }
+
+    /// Access the files as requested by a message, and report how it went
+    pub fn run_file_action(&mut self, action: FileAction) {
+        self.notice = Some(match action {
+            FileAction::Save(path) => match self.save(&path) {
+                Ok(()) => format!("Saved to {}", path.display()),
+                Err(err) => format!("Failed to save {}: {err}", path.display()),
+            },
+            FileAction::Open(path) => match self.open(&path) {
+                Ok(()) => format!("Opened {}", path.display()),
+                Err(err) => format!("Failed to open {}: {err}", path.display()),
+            },
+            FileAction::ExportReport(path, csv) => match fs::write(&path, csv) {
+                Ok(()) => format!("Exported the report to {}", path.display()),
+                Err(err) => format!("Failed to export the report to {}: {err}", path.display()),
+            },
+        });
+        self.refresh_analyses();
+    }
+
+    /// Payload holding the selected shapes, `None` when nothing is selected
+    fn selection_payload(&self) -> Option<Payload> {
+        let items = self
+            .selection()
+            .into_iter()
+            .filter_map(|shape_id| {
+                Some(ClipboardItem {
+                    shape: self.plan.get_shape(shape_id)?.clone(),
+                    metadata: self.plan.get_metadata(shape_id).cloned(),
+                })
+            })
+            .collect();
+
+        Payload::new(items)
+    }
+
+    fn copy_selection(&mut self) {
+        if let Some(payload) = self.selection_payload() {
+            self.clipboard_action = Some(ClipboardAction::Write(payload.to_text()));
+        }
+    }
+
+    fn delete_selection(&mut self) {
+        if let Some(command) = command::remove_shapes(&self.plan, &self.selection()) {
+            self.do_command(command);
+        }
+        self.selection.clear();
+    }
+
+    /// Add the shapes of the `payload` with fresh ids, with their top left corner
+    /// at `target` or at the nearest free spot. The new shapes are selected
+    fn paste(&mut self, payload: &Payload, target: Vec2) {
+        let shapes: Vec<_> = payload.items().iter().map(|item| item.shape.clone()).collect();
+        let Some(offset) = self.plan.first_free_spot(&shapes, target) else {
+            self.notice = Some("No free spot for the pasted shapes".into());
+            return;
+        };
+
+        let shapes: Vec<_> = payload
+            .items()
+            .iter()
+            .map(|item| {
+                let mut shape = item.shape.clone();
+                shape.translate(offset);
+                (self.plan.generate_shape_id(), shape, item.metadata.clone())
+            })
+            .collect();
+
+        self.selection = shapes.iter().map(|(id, _, _)| *id).collect();
+        self.do_command(command::add_shapes(shapes));
+    }
+
+    /// Copies of the selected shapes laid out by the array draft, each with
+    /// whether it fits in the plan. Empty when no valid array is being prepared
+    pub fn array_preview(&self) -> Vec<(Vec<Shape>, bool)> {
+        let Some(layout) = self.array_draft.as_ref().and_then(ArrayDraft::layout) else {
+            return Vec::new();
+        };
+        let shapes: Vec<_> = self
+            .selection()
+            .into_iter()
+            .filter_map(|shape_id| self.plan.get_shape(shape_id).cloned())
+            .collect();
+
+        layout
+            .offsets(&shapes)
+            .into_iter()
+            .map(|offset| {
+                let copy: Vec<_> = shapes
+                    .iter()
+                    .map(|shape| {
+                        let mut shape = shape.clone();
+                        shape.translate(offset);
+                        shape
+                    })
+                    .collect();
+                let fits = shape::are_in_plan(&copy) && self.plan.are_disjoint(&copy, &[]);
+                (copy, fits)
+            })
+            .collect()
+    }
+
+    /// Add the copies of the array preview which fit in the plan, as a single
+    /// command. The copies overlapping other shapes or reaching negative
+    /// coordinates are skipped and reported
+    fn apply_array(&mut self) {
+        let Some(draft) = &self.array_draft else {
+            return;
+        };
+        if draft.layout().is_none() {
+            self.notice = Some("Counts must be whole numbers but 0, gaps non-negative".into());
+            return;
+        }
+
+        let metadata: Vec<_> = self
+            .selection()
+            .into_iter()
+            .map(|shape_id| self.plan.get_metadata(shape_id).cloned())
+            .collect();
+        let preview = self.array_preview();
+        if preview.is_empty() {
+            self.notice = Some("The array needs a selection and more than one cell".into());
+            return;
+        }
+        let skipped = preview.iter().filter(|(_, fits)| !fits).count();
+        let outside = preview.iter().filter(|(copy, _)| !shape::are_in_plan(copy)).count();
+
+        let shapes: Vec<_> = preview
+            .into_iter()
+            .filter(|(_, fits)| *fits)
+            .flat_map(|(copy, _)| copy.into_iter().zip(metadata.iter().cloned()))
+            .map(|(shape, metadata)| (self.plan.generate_shape_id(), shape, metadata))
+            .collect();
+        if shapes.is_empty() {
+            self.notice = Some("Every copy would overlap other shapes or leave the plan".into());
+            return;
+        }
+
+        if skipped > 0 {
+            self.notice = Some(format!(
+                "{} overlapping copies and {outside} copies outside of the plan were skipped",
+                skipped - outside
+            ));
+        }
+        self.do_command(command::add_shapes(shapes));
+        self.array_draft = None;
+    }
+
+    /// Selected shapes mirrored about the axis at half the coordinate `axis`
+    pub fn mirrored_selection(&self, flip: Flip, axis: i32) -> Vec<(ShapeId, Shape)> {
+        self.selection()
+            .into_iter()
+            .filter_map(|shape_id| {
+                let mut shape = self.plan.get_shape(shape_id)?.clone();
+                shape.mirror(flip, axis);
+                Some((shape_id, shape))
+            })
+            .collect()
+    }
+
+    /// Mirror the selected shapes unless they would overlap other shapes or
+    /// leave the plan, whose coordinates are positive.
+    /// Return whether they were mirrored
+    fn mirror_selection(&mut self, flip: Flip, axis: i32) -> bool {
+        let (shape_ids, shapes): (Vec<_>, Vec<_>) =
+            self.mirrored_selection(flip, axis).into_iter().unzip();
+        if shape_ids.is_empty() {
+            return false;
+        }
+        if !shape::are_in_plan(&shapes) {
+            self.notice = Some("The mirrored shapes would leave the plan".into());
+            return false;
+        }
+        if !self.plan.are_disjoint(&shapes, &shape_ids) {
+            self.notice = Some("The mirrored shapes would overlap other shapes".into());
+            return false;
+        }
+
+        self.do_command(command::mirror(flip, axis, shape_ids));
+        true
+    }
+
+    fn group(&mut self, shape_ids: Vec<ShapeId>) {
+        if shape_ids.len() < 2 {
+            self.notice = Some("A group needs at least two shapes".into());
+            return;
+        }
+
+        let group_id = self.plan.generate_group_id();
+        let shapes = shape_ids
+            .into_iter()
+            .map(|id| (id, self.plan.get_group(id)))
+            .collect();
+        self.do_command(command::group(group_id, shapes));
+    }
+
+    fn ungroup(&mut self, shape_ids: Vec<ShapeId>) {
+        let shapes: Vec<_> = shape_ids
+            .into_iter()
+            .filter_map(|id| Some((id, self.plan.get_group(id)?)))
+            .collect();
+        if shapes.is_empty() {
+            self.notice = Some("No group in this area".into());
+            return;
+        }
+
+        self.do_command(command::ungroup(shapes));
+    }
+
+    fn add_instance(&mut self, block_id: BlockId, pos: Vec2) {
+        let Some(block) = self.plan.get_block(block_id) else {
+            return;
+        };
+        let name = block.name().to_string();
+        let shapes = block.shapes_at(pos);
+        if !self.plan.are_disjoint(&shapes, &[]) {
+            return;
+        }
+
+        let shapes = shapes
+            .into_iter()
+            .map(|shape| (self.plan.generate_shape_id(), shape))
+            .collect();
+        self.do_command(command::add_instance(block_id, name, shapes));
+    }
+
+    /// Apply the dimensions of the draft to the block and all its instances.
+    /// The shapes of the instances keep their position, so an instance whose
+    /// shapes were moved individually stays arranged the same way
+    fn apply_block_edit(&mut self) {
+        let Some(draft) = &self.block_draft else {
+            return;
+        };
+        let block_id = draft.block_id();
+        let Some(old) = self.plan.get_block(block_id).cloned() else {
+            self.block_draft = None;
+            return;
+        };
+        let Some(new) = draft.apply(&old) else {
+            self.notice = Some("Dimensions must be positive whole numbers".into());
+            return;
+        };
+
+        let shapes: Vec<(ShapeId, Shape, Shape)> = self
+            .plan
+            .block_members(block_id)
+            .filter_map(|(shape_id, index)| {
+                let old_shape = self.plan.get_shape(shape_id)?;
+                let mut new_shape = new.shapes().get(index)?.clone();
+                new_shape.move_to(old_shape.pos());
+                Some((shape_id, old_shape.clone(), new_shape))
+            })
+            .collect();
+
+        let ids: Vec<_> = shapes.iter().map(|(id, _, _)| *id).collect();
+        let new_shapes: Vec<_> = shapes.iter().map(|(_, _, shape)| shape.clone()).collect();
+        if !self.plan.are_disjoint(&new_shapes, &ids) {
+            self.notice = Some(format!(
+                "Can't edit {}: its instances would overlap other shapes",
+                old.name()
+            ));
+            return;
+        }
+
+        self.do_command(command::edit_block(block_id, old, new, shapes));
+        self.block_draft = None;
+    }
+
+    /// Save the plan along with its history
+    pub fn save(&self, path: &Path) -> Result<(), DocumentError> {
+        document::save(path, &self.plan, &self.history)
+    }
+
+    /// Replace the plan and its history by the ones saved in the document at `path`
+    pub fn open(&mut self, path: &Path) -> Result<(), DocumentError> {
+        let (plan, mut history) = document::load(path)?;
+        history.set_config(self.history.config());
+
+        self.plan = plan;
+        self.revision += 1;
+        self.history = history;
+        self.state = ControllerState::Idle;
+
+        Ok(())
+    }
+
+    /// Selected shapes which are still in the plan
+    pub fn selection(&self) -> Vec<ShapeId> {
+        self.selection
+            .iter()
+            .copied()
+            .filter(|shape_id| self.plan.get_shape(*shape_id).is_some())
+            .collect()
+    }
+
+    pub fn has_selection(&self) -> bool {
+        self.idle() && !self.selection().is_empty()
+    }
+
+    pub fn has_single_selection(&self) -> bool {
+        self.idle() && self.selection().len() == 1
+    }
+
+    pub fn access_draft(&self) -> &AccessDraft {
+        &self.access_draft
+    }
+
+    pub fn entrance(&self) -> Option<Vec2> {
+        self.entrance
+    }
+
+    pub fn accessibility(&self) -> Option<&Accessibility> {
+        self.accessibility.get()
+    }
+
+    pub fn egress_limit(&self) -> &str {
+        &self.egress_limit
+    }
+
+    pub fn egress(&self) -> Option<&Egress> {
+        self.egress.get()
+    }
+
+    pub fn coverage_category(&self) -> Option<&str> {
+        self.coverage_category.as_deref()
+    }
+
+    pub fn coverage_obstacles(&self) -> bool {
+        self.coverage_obstacles
+    }
+
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.get()
+    }
+
+    pub fn report(&self) -> Option<&Report> {
+        self.report.get()
+    }
+
+    pub fn motion_path(&self) -> Option<&[Shape]> {
+        self.motion_path.as_deref()
+    }
+
+    /// Take the access to the system clipboard requested by the last message
+    pub fn take_clipboard_action(&mut self) -> Option<ClipboardAction> {
+        self.clipboard_action.take()
+    }
+
+    /// Take the access to the files requested by the last message
+    pub fn take_file_action(&mut self) -> Option<FileAction> {
+        self.file_action.take()
+    }
+
+    pub fn array_draft(&self) -> Option<&ArrayDraft> {
+        self.array_draft.as_ref()
+    }
+
+    pub fn optimizer_draft(&self) -> Option<&OptimizerDraft> {
+        self.optimizer_draft.as_ref()
+    }
+
+    /// Layout found by the optimizer, as long as the shapes it moves
+    /// haven't changed since and it moves at least one shape
+    pub fn optimization_preview(&self) -> Option<&OptimizationPreview> {
+        self.optimization.as_ref().filter(|preview| {
+            !preview.shapes.is_empty()
+                && preview
+                    .shapes
+                    .iter()
+                    .all(|(shape_id, old, _)| self.plan.get_shape(*shape_id) == Some(old))
+        })
+    }
+
+    pub fn packing_draft(&self) -> &PackingDraft {
+        &self.packing_draft
+    }
+
+    pub fn block_draft(&self) -> Option<&BlockDraft> {
+        self.block_draft.as_ref()
+    }
+
+    pub fn catalog(&self) -> &Catalog {
+        &self.catalog
+    }
+
+    pub fn file_path(&self) -> &str {
+        &self.file_path
+    }
+
+    pub fn notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
     pub fn can_undo(&self) -> bool {
-        self.idle() && !self.done_commands.is_empty()
+        self.gesture_in_progress || self.history.can_undo()
     }
 
     pub fn can_redo(&self) -> bool {
-        self.idle() && !self.undone_commands.is_empty()
+        self.history.can_redo()
+    }
+
+    pub fn can_switch_branch(&self) -> bool {
+        self.history.can_switch_branch()
+    }
+
+    /// Check if a canvas program is in the middle of a gesture, like sizing a new
+    /// shape or dragging one. When the gesture is cancelled by the controller, the
+    /// programs must drop their gesture state
+    pub fn gesture_in_progress(&self) -> bool {
+        self.gesture_in_progress
     }
 
     pub fn do_command(&mut self, command: BoxedCommand) {
-        command.apply(&mut self.plan);
-        // When we execute a new command, we can't redo anymore
-        self.undone_commands.clear();
-        self.done_commands.push(command);
+        self.history.push(command, &mut self.plan, self.time);
+        self.revision += 1;
     }
 
     pub fn undo(&mut self) {
-        if let Some(command) = self.done_commands.pop() {
-            command.undo(&mut self.plan);
-            self.undone_commands.push(command);
-        }
+        self.history.undo(&mut self.plan);
+        self.revision += 1;
     }
 
     pub fn redo(&mut self) {
-        if let Some(command) = self.undone_commands.pop() {
-            command.apply(&mut self.plan);
-            self.done_commands.push(command);
-        }
+        self.history.redo(&mut self.plan);
+        self.revision += 1;
+    }
+
+    /// The commands leading to the current state followed by
+    /// the ones that can be redone
+    pub fn history(&self) -> Vec<HistoryEntry> {
+        self.history.entries()
+    }
+
+    /// Number of done commands, the position of the current state in the history
+    pub fn history_position(&self) -> usize {
+        self.history.position()
     }
 
     pub fn state(&self) -> &ControllerState {
@@ -106,4 +1199,289 @@ impl Controller {
     pub fn scale(&self) -> f32 {
         self.scale
     }
+
+    /// Position of the cursor in world units, `None` when it is outside of the plan
+    pub fn cursor(&self) -> Option<Vec2> {
+        self.cursor
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::shape::{Circle, Rectangle};
+
+    #[test]
+    fn jump_to_history_undoes_and_redoes() {
+        let mut controller = Controller::default();
+        controller.update(Message::AddShape(Rectangle::new((0, 0).into(), 2, 2).into()));
+        controller.update(Message::AddShape(Circle::new((10, 10).into(), 2).into()));
+        controller.update(Message::AddShape(Circle::new((20, 20).into(), 2).into()));
+
+        controller.update(Message::JumpToHistory(1));
+        assert_eq!(controller.history_position(), 1);
+        assert_eq!(controller.plan().iter().count(), 1);
+
+        controller.update(Message::JumpToHistory(3));
+        assert_eq!(controller.history_position(), 3);
+        assert_eq!(controller.plan().iter().count(), 3);
+
+        let history: Vec<_> = controller
+            .history()
+            .into_iter()
+            .map(|entry| entry.description)
+            .collect();
+        assert_eq!(history, ["Add rectangle", "Add circle", "Add circle"]);
+    }
+
+    #[test]
+    fn undo_during_gesture_cancels_it_first() {
+        let mut controller = Controller::default();
+        controller.update(Message::AddShape(Rectangle::new((0, 0).into(), 2, 2).into()));
+        controller.update(Message::MoveButton);
+        controller.update(Message::GestureStarted);
+        assert!(controller.can_undo());
+
+        controller.update(Message::Undo);
+        assert!(!controller.gesture_in_progress());
+        assert_eq!(controller.plan().iter().count(), 1);
+
+        controller.update(Message::Undo);
+        assert_eq!(controller.plan().iter().count(), 0);
+        assert!(matches!(controller.state(), ControllerState::MovingShapes));
+    }
+
+    fn shapes(controller: &Controller) -> Vec<Shape> {
+        let mut shapes: Vec<_> = controller.plan().iter().map(|(_, s)| s.clone()).collect();
+        shapes.sort_by_key(|shape| shape.pos());
+        shapes
+    }
+
+    #[test]
+    fn mirror_stays_in_plan() {
+        let mut controller = Controller::default();
+        let shape = Rectangle::new((2, 0).into(), 4, 2);
+        controller.update(Message::AddShape(shape.clone().into()));
+        let (id, _) = controller.plan().iter().next().unwrap().clone();
+        controller.update(Message::Select(vec![id]));
+        controller.update(Message::MirrorButton(Flip::Horizontal));
+
+        controller.update(Message::Mirror(Flip::Horizontal, 1));
+        assert!(controller.notice().is_some());
+        assert_eq!(controller.plan().get_shape(id), Some(&shape.into()));
+        assert!(matches!(controller.state(), ControllerState::Mirroring(_)));
+
+        controller.update(Message::Mirror(Flip::Horizontal, 6));
+        assert_eq!(
+            controller.plan().get_shape(id),
+            Some(&Rectangle::new((6, 0).into(), 4, 2).into())
+        );
+    }
+
+    #[test]
+    fn packing_is_capped() {
+        let mut controller = Controller::default();
+        controller.set_catalog(
+            Catalog::from_str(r#"[{ "name": "Desk", "shape": { "Circle": { "radius": 1 } } }]"#)
+                .unwrap(),
+        );
+        controller.update(Message::SetRoomButton);
+        controller.update(Message::AreaSelected((0, 0).into(), (20, 20).into()));
+
+        controller.update(Message::PackQuantityChanged(0, "501".into()));
+        controller.update(Message::PackButton);
+        assert!(controller.notice().unwrap().contains("at most 500 items"));
+        assert_eq!(controller.plan().iter().count(), 0);
+
+        controller.update(Message::PackQuantityChanged(0, "2".into()));
+        controller.update(Message::PackButton);
+        assert_eq!(controller.plan().iter().count(), 2);
+
+        // Quantities of entries missing from the catalog are ignored
+        controller.update(Message::PackQuantityChanged(usize::MAX, "2".into()));
+        controller.update(Message::PackQuantityChanged(1, "2".into()));
+        assert_eq!(controller.packing_draft().quantity(1), "");
+    }
+
+    #[test]
+    fn editing_block_updates_instances() {
+        let mut controller = Controller::default();
+        controller.update(Message::AddShape(Rectangle::new((0, 0).into(), 4, 2).into()));
+        controller.update(Message::CreateBlockButton);
+        controller.update(Message::AreaSelected((0, 0).into(), (5, 5).into()));
+        let (block_id, _) = controller.plan().blocks().next().unwrap().clone();
+        controller.update(Message::BlockButton(block_id));
+        controller.update(Message::AddInstance(block_id, (0, 10).into()));
+        controller.update(Message::Cancel);
+        controller.update(Message::AddShape(Circle::new((20, 0).into(), 2).into()));
+
+        // The blocks can't be edited while placing an instance
+        controller.update(Message::BlockButton(block_id));
+        controller.update(Message::EditBlockButton(block_id));
+        assert!(controller.block_draft().is_none());
+        controller.update(Message::Cancel);
+
+        controller.update(Message::EditBlockButton(block_id));
+        controller.update(Message::BlockDraftChanged(0, 0, "6".into()));
+        controller.update(Message::ApplyBlockEdit);
+        assert!(controller.block_draft().is_none());
+        assert_eq!(
+            shapes(&controller),
+            [
+                Rectangle::new((0, 0).into(), 6, 2).into(),
+                Rectangle::new((0, 10).into(), 6, 2).into(),
+                Circle::new((20, 0).into(), 2).into(),
+            ]
+        );
+
+        controller.update(Message::Undo);
+        assert_eq!(
+            controller.plan().get_block(block_id).unwrap().shapes(),
+            [Rectangle::new((0, 0).into(), 4, 2).into()]
+        );
+        assert_eq!(shapes(&controller)[1], Rectangle::new((0, 10).into(), 4, 2).into());
+
+        // Growing the block up to the circle is rejected
+        controller.update(Message::EditBlockButton(block_id));
+        controller.update(Message::BlockDraftChanged(0, 0, "19".into()));
+        controller.update(Message::ApplyBlockEdit);
+        assert!(controller.notice().is_some());
+        assert!(controller.block_draft().is_some());
+        assert_eq!(shapes(&controller)[0], Rectangle::new((0, 0).into(), 4, 2).into());
+    }
+
+    #[test]
+    fn array_goes_left_within_plan() {
+        let mut controller = Controller::default();
+        controller.update(Message::AddShape(Rectangle::new((4, 0).into(), 2, 2).into()));
+        let (shape_id, _) = controller.plan().iter().next().unwrap().clone();
+        controller.update(Message::Select(vec![shape_id]));
+
+        controller.update(Message::ArrayButton);
+        controller.update(Message::ArrayDraftChanged(0, "-3".into()));
+        let fits: Vec<_> = controller.array_preview().into_iter().map(|(_, fits)| fits).collect();
+        assert_eq!(fits, [true, false]);
+
+        controller.update(Message::ApplyArray);
+        assert!(controller.notice().unwrap().contains("1 copies outside of the plan"));
+        assert_eq!(
+            shapes(&controller),
+            [
+                Rectangle::new((1, 0).into(), 2, 2).into(),
+                Rectangle::new((4, 0).into(), 2, 2).into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn array_skips_overlapping_copies() {
+        let mut controller = Controller::default();
+        controller.update(Message::AddShape(Rectangle::new((0, 0).into(), 2, 2).into()));
+        controller.update(Message::AddShape(Rectangle::new((6, 1).into(), 1, 1).into()));
+        let (shape_id, _) = controller.plan().iter().next().unwrap().clone();
+        controller.update(Message::Select(vec![shape_id]));
+
+        controller.update(Message::ArrayButton);
+        controller.update(Message::ArrayDraftChanged(0, "4".into()));
+        let fits: Vec<_> = controller.array_preview().into_iter().map(|(_, fits)| fits).collect();
+        assert_eq!(fits, [true, false, true]);
+
+        controller.update(Message::ApplyArray);
+        assert!(controller.array_draft().is_none());
+        assert!(controller.notice().is_some());
+        assert_eq!(
+            shapes(&controller),
+            [
+                Rectangle::new((0, 0).into(), 2, 2).into(),
+                Rectangle::new((3, 0).into(), 2, 2).into(),
+                Rectangle::new((6, 1).into(), 1, 1).into(),
+                Rectangle::new((9, 0).into(), 2, 2).into(),
+            ]
+        );
+
+        controller.update(Message::Undo);
+        assert_eq!(controller.plan().iter().count(), 2);
+    }
+
+    #[test]
+    fn flip_selection_about_its_center() {
+        let mut controller = Controller::default();
+        controller.update(Message::AddShape(Rectangle::new((0, 0).into(), 2, 2).into()));
+        controller.update(Message::AddShape(Circle::new((6, 3).into(), 1).into()));
+        let ids: Vec<_> = controller.plan().iter().map(|(id, _)| *id).collect();
+        controller.update(Message::Select(ids.clone()));
+
+        controller.update(Message::FlipButton(Flip::Horizontal));
+        assert_eq!(
+            shapes(&controller),
+            [
+                Circle::new((1, 3).into(), 1).into(),
+                Rectangle::new((5, 0).into(), 2, 2).into(),
+            ]
+        );
+
+        // The circle would land on the new rectangle
+        controller.update(Message::AddShape(Rectangle::new((0, 0).into(), 1, 1).into()));
+        controller.update(Message::FlipButton(Flip::Vertical));
+        assert!(controller.notice().is_some());
+        assert_eq!(controller.history().len(), 4);
+
+        controller.update(Message::Undo);
+        controller.update(Message::Undo);
+        assert_eq!(
+            controller.plan().get_shape(ids[0]),
+            Some(&Rectangle::new((0, 0).into(), 2, 2).into())
+        );
+    }
+
+    #[test]
+    fn optimization_is_previewed_then_applied() {
+        let mut controller = Controller::default();
+        controller.update(Message::AddShape(Rectangle::new((10, 10).into(), 2, 2).into()));
+        controller.update(Message::AddShape(Rectangle::new((12, 10).into(), 2, 2).into()));
+        let before = shapes(&controller);
+
+        controller.update(Message::OptimizeButton);
+        controller.update(Message::OptimizerDraftChanged(1, "300".into()));
+        controller.update(Message::PreviewOptimization);
+        let preview = controller.optimization_preview().unwrap().clone();
+        assert!(preview.cost < preview.initial_cost);
+        assert_eq!(shapes(&controller), before);
+
+        // The same seed proposes the same layout
+        controller.update(Message::PreviewOptimization);
+        assert_eq!(controller.optimization_preview().unwrap().shapes, preview.shapes);
+
+        controller.update(Message::ApplyOptimization);
+        assert!(controller.optimizer_draft().is_none());
+        assert_ne!(shapes(&controller), before);
+        assert_eq!(controller.history().last().unwrap().description, "Optimize layout of 2 shapes");
+
+        controller.update(Message::Undo);
+        assert_eq!(shapes(&controller), before);
+    }
+
+    #[test]
+    fn report_follows_plan_and_exports_csv() {
+        let path = std::env::temp_dir().join(format!("placo-report-{}.json", std::process::id()));
+        let mut controller = Controller::default();
+        controller.update(Message::FilePathChanged(path.display().to_string()));
+        controller.update(Message::AddShape(Rectangle::new((0, 0).into(), 4, 2).into()));
+
+        controller.update(Message::ReportButton);
+        assert_eq!(controller.report().unwrap().shape_count, 1);
+        controller.update(Message::AddShape(Rectangle::new((10, 0).into(), 2, 2).into()));
+        assert_eq!(controller.report().unwrap().shape_area, 12.0);
+
+        controller.update(Message::ExportReportButton);
+        let Some(FileAction::ExportReport(csv_path, csv)) = controller.take_file_action() else {
+            panic!("the report isn't exported");
+        };
+        assert_eq!(csv_path, path.with_extension("csv"));
+        assert!(csv.contains("Shapes,2,12.00,,,,"));
+        assert!(controller.take_file_action().is_none());
+
+        controller.update(Message::ReportButton);
+        assert!(controller.report().is_none());
+    }
 }
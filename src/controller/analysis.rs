@@ -0,0 +1,78 @@
+//! Analyses of the plan shown while their mode is active. They go over the
+//! whole free space, so they are only computed again when the plan or their
+//! parameters change
+
+/// Result of an analysis, with the revision of the plan and the parameters
+/// it was computed for
+#[derive(Debug)]
+pub struct Analysis<P, T> {
+    inputs: Option<(u64, P)>,
+    result: Option<T>,
+}
+
+impl<P, T> Default for Analysis<P, T> {
+    fn default() -> Self {
+        Self {
+            inputs: None,
+            result: None,
+        }
+    }
+}
+
+impl<P: PartialEq, T> Analysis<P, T> {
+    /// Compute the analysis of the plan at `revision` with `params`, unless it
+    /// was already computed for them. `None` parameters drop the result, for
+    /// hidden analyses or invalid parameters. Return whether it was computed
+    pub fn refresh(
+        &mut self,
+        revision: u64,
+        params: Option<P>,
+        compute: impl FnOnce(&P) -> Option<T>,
+    ) -> bool {
+        let Some(params) = params else {
+            self.inputs = None;
+            self.result = None;
+            return false;
+        };
+
+        let inputs = (revision, params);
+        if self.inputs.as_ref() == Some(&inputs) {
+            return false;
+        }
+        self.result = compute(&inputs.1);
+        self.inputs = Some(inputs);
+        true
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        self.result.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn computed_again_only_on_change() {
+        let mut analysis = Analysis::default();
+        let mut runs = 0;
+        let mut refresh = |analysis: &mut Analysis<u32, u32>, revision, params| {
+            analysis.refresh(revision, params, |params| {
+                runs += 1;
+                Some(params * 2)
+            });
+        };
+
+        refresh(&mut analysis, 0, Some(1));
+        refresh(&mut analysis, 0, Some(1));
+        assert_eq!(analysis.get(), Some(&2));
+        refresh(&mut analysis, 1, Some(1));
+        refresh(&mut analysis, 1, Some(3));
+        assert_eq!(analysis.get(), Some(&6));
+        refresh(&mut analysis, 1, None);
+        assert_eq!(analysis.get(), None);
+        refresh(&mut analysis, 1, Some(3));
+        assert_eq!(runs, 4);
+    }
+}
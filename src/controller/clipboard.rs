@@ -0,0 +1,101 @@
+//! Copied shapes, serialized as text in the system clipboard so they
+//! can be pasted in another running instance
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::metadata::Metadata;
+use crate::model::shape::{self, Shape};
+use crate::model::vec2::Vec2;
+
+/// Tag of the payloads, to recognize them among any text of the clipboard
+const FORMAT: &str = "placo-shapes";
+
+/// Request for the application to access the system clipboard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardAction {
+    Write(String),
+    /// The content is sent back with `Message::Paste`
+    Read,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipboardItem {
+    pub shape: Shape,
+    #[serde(default)]
+    pub metadata: Option<Metadata>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Payload {
+    format: String,
+    /// Top left corner of the bounding box of the copied shapes
+    origin: Vec2,
+    /// Shapes relative to the origin
+    items: Vec<ClipboardItem>,
+}
+
+impl Payload {
+    /// Payload of the `items`, `None` when there is no item
+    pub fn new(mut items: Vec<ClipboardItem>) -> Option<Self> {
+        let (origin, _) = shape::bounding_box(items.iter().map(|item| &item.shape))?;
+        for item in &mut items {
+            item.shape.translate(-origin);
+        }
+
+        Some(Self {
+            format: FORMAT.into(),
+            origin,
+            items,
+        })
+    }
+
+    pub fn origin(&self) -> Vec2 {
+        self.origin
+    }
+
+    /// Items relative to the origin
+    pub fn items(&self) -> &[ClipboardItem] {
+        &self.items
+    }
+
+    pub fn to_text(&self) -> String {
+        serde_json::to_string(self).expect("payloads are always serializable")
+    }
+
+    /// Parse the content of the clipboard, `None` if it isn't a payload
+    pub fn from_text(text: &str) -> Option<Self> {
+        serde_json::from_str(text)
+            .ok()
+            .filter(|payload: &Self| payload.format == FORMAT && !payload.items.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::shape::{Circle, Rectangle};
+
+    #[test]
+    fn payload_survives_text() {
+        let items = vec![
+            ClipboardItem {
+                shape: Rectangle::new((10, 5).into(), 4, 2).into(),
+                metadata: Some(Metadata {
+                    name: Some("Desk".into()),
+                    category: None,
+                }),
+            },
+            ClipboardItem {
+                shape: Circle::new((12, 10).into(), 1).into(),
+                metadata: None,
+            },
+        ];
+        let payload = Payload::new(items).unwrap();
+        assert_eq!(payload.origin(), (10, 5).into());
+        assert_eq!(payload.items()[1].shape, Circle::new((2, 5).into(), 1).into());
+
+        assert_eq!(Payload::from_text(&payload.to_text()), Some(payload));
+        assert_eq!(Payload::from_text("some text"), None);
+        assert_eq!(Payload::from_text(r#"{"format":"other","origin":{"x":0,"y":0},"items":[]}"#), None);
+    }
+}
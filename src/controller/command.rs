@@ -1,18 +1,116 @@
 pub mod add;
+pub mod block;
+pub mod dimension;
+pub mod exits;
+pub mod group;
+pub mod mirror;
+pub mod remove;
+pub mod room;
 pub mod update;
 
 use core::fmt;
+use std::any::Any;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::model::plan::Plan;
 
-pub use add::add_shape;
-pub use update::update_shape;
+pub use add::{add_item, add_shape, add_shapes};
+pub use block::{add_instance, create_block, edit_block};
+pub use dimension::{add_dimension, remove_dimension};
+pub use exits::set_exits;
+pub use group::{group, ungroup};
+pub use mirror::mirror;
+pub use remove::remove_shapes;
+pub use room::set_room;
+pub use update::{update_shape, update_shapes};
+
+use self::add::{AddShape, AddShapes};
+use self::block::{AddInstance, CreateBlock, EditBlock};
+use self::dimension::{AddDimension, RemoveDimension};
+use self::exits::SetExits;
+use self::group::{Group, Ungroup};
+use self::mirror::Mirror;
+use self::remove::RemoveShapes;
+use self::room::SetRoom;
+use self::update::{UpdateShape, UpdateShapes};
 
-pub trait Command: fmt::Debug {
+pub trait Command: fmt::Debug + Any {
     fn apply(&self, plan: &mut Plan);
     fn undo(&self, plan: &mut Plan);
+    /// Human-readable description of the command, shown in the history
+    fn description(&self) -> String;
+
+    /// Try to merge the `next` command, already applied after this one,
+    /// into this command so they form a single undo step.
+    /// Return `false` if the commands can't be merged
+    fn coalesce(&mut self, _next: &dyn Command) -> bool {
+        false
+    }
+
+    /// Approximation of the memory used by the command, in bytes
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+
+    /// Serializable copy of the command
+    fn record(&self) -> CommandRecord;
 }
 
 pub type BoxedCommand = Box<dyn Command + 'static>;
 
+/// Serializable form of every command, used to save the history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CommandRecord {
+    AddShape(AddShape),
+    UpdateShape(UpdateShape),
+    AddDimension(AddDimension),
+    RemoveDimension(RemoveDimension),
+    CreateBlock(CreateBlock),
+    AddInstance(AddInstance),
+    EditBlock(EditBlock),
+    UpdateShapes(UpdateShapes),
+    Group(Group),
+    Ungroup(Ungroup),
+    AddShapes(AddShapes),
+    RemoveShapes(RemoveShapes),
+    Mirror(Mirror),
+    SetRoom(SetRoom),
+    SetExits(SetExits),
+}
+
+impl CommandRecord {
+    pub fn into_command(self) -> BoxedCommand {
+        match self {
+            CommandRecord::AddShape(command) => Box::new(command),
+            CommandRecord::UpdateShape(command) => Box::new(command),
+            CommandRecord::AddDimension(command) => Box::new(command),
+            CommandRecord::RemoveDimension(command) => Box::new(command),
+            CommandRecord::CreateBlock(command) => Box::new(command),
+            CommandRecord::AddInstance(command) => Box::new(command),
+            CommandRecord::EditBlock(command) => Box::new(command),
+            CommandRecord::UpdateShapes(command) => Box::new(command),
+            CommandRecord::Group(command) => Box::new(command),
+            CommandRecord::Ungroup(command) => Box::new(command),
+            CommandRecord::AddShapes(command) => Box::new(command),
+            CommandRecord::RemoveShapes(command) => Box::new(command),
+            CommandRecord::Mirror(command) => Box::new(command),
+            CommandRecord::SetRoom(command) => Box::new(command),
+            CommandRecord::SetExits(command) => Box::new(command),
+        }
+    }
+}
+
+impl Serialize for dyn Command {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.record().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BoxedCommand {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        CommandRecord::deserialize(deserializer).map(CommandRecord::into_command)
+    }
+}
+
 
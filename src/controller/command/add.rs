@@ -1,24 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::metadata::Metadata;
 use crate::model::plan::{ShapeId, Plan};
 use crate::model::shape::Shape;
 
-use super::{Command, BoxedCommand};
+use super::{BoxedCommand, Command, CommandRecord};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddShape {
     shape_id: ShapeId,
     shape: Shape,
+    #[serde(default)]
+    metadata: Option<Metadata>,
 }
 
 pub fn add_shape(shape_id: ShapeId, shape: Shape) -> BoxedCommand {
-    Box::new(AddShape { shape_id, shape })
+    Box::new(AddShape {
+        shape_id,
+        shape,
+        metadata: None,
+    })
+}
+
+/// Add a shape described by `metadata`, like an item of the catalog
+pub fn add_item(shape_id: ShapeId, shape: Shape, metadata: Metadata) -> BoxedCommand {
+    Box::new(AddShape {
+        shape_id,
+        shape,
+        metadata: Some(metadata),
+    })
 }
 
 impl Command for AddShape {
     fn apply(&self, plan: &mut Plan) {
         plan.add_shape(self.shape_id, self.shape.clone());
+        if let Some(metadata) = &self.metadata {
+            plan.set_metadata(self.shape_id, metadata.clone());
+        }
     }
 
     fn undo(&self, plan: &mut Plan) {
         plan.remove_shape(self.shape_id);
     }
+
+    fn description(&self) -> String {
+        match self.metadata.as_ref().and_then(|metadata| metadata.name.as_ref()) {
+            Some(name) => format!("Add {name}"),
+            None => format!("Add {}", self.shape.name().to_lowercase()),
+        }
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::AddShape(self.clone())
+    }
+}
+
+/// Add several shapes in a single step, like pasted shapes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddShapes {
+    shapes: Vec<(ShapeId, Shape, Option<Metadata>)>,
+}
+
+pub fn add_shapes(shapes: Vec<(ShapeId, Shape, Option<Metadata>)>) -> BoxedCommand {
+    Box::new(AddShapes { shapes })
+}
+
+impl Command for AddShapes {
+    fn apply(&self, plan: &mut Plan) {
+        for (shape_id, shape, metadata) in &self.shapes {
+            plan.add_shape(*shape_id, shape.clone());
+            if let Some(metadata) = metadata {
+                plan.set_metadata(*shape_id, metadata.clone());
+            }
+        }
+    }
+
+    fn undo(&self, plan: &mut Plan) {
+        for (shape_id, _, _) in &self.shapes {
+            plan.remove_shape(*shape_id);
+        }
+    }
+
+    fn description(&self) -> String {
+        format!("Add {} shapes", self.shapes.len())
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self) + std::mem::size_of_val(self.shapes.as_slice())
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::AddShapes(self.clone())
+    }
 }
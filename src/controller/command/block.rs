@@ -0,0 +1,163 @@
+use std::mem;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::block::{Block, BlockMember};
+use crate::model::plan::{BlockId, Plan, ShapeId};
+use crate::model::shape::Shape;
+
+use super::{BoxedCommand, Command, CommandRecord};
+
+/// Define a block from shapes of the plan, which become its first instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBlock {
+    block_id: BlockId,
+    block: Block,
+    /// Shapes of the first instance, in the order of the definition
+    members: Vec<ShapeId>,
+}
+
+pub fn create_block(block_id: BlockId, block: Block, members: Vec<ShapeId>) -> BoxedCommand {
+    Box::new(CreateBlock {
+        block_id,
+        block,
+        members,
+    })
+}
+
+impl Command for CreateBlock {
+    fn apply(&self, plan: &mut Plan) {
+        plan.add_block(self.block_id, self.block.clone());
+        for (index, shape_id) in self.members.iter().enumerate() {
+            let member = BlockMember {
+                block_id: self.block_id,
+                index,
+            };
+            plan.set_block_member(*shape_id, member);
+        }
+    }
+
+    fn undo(&self, plan: &mut Plan) {
+        plan.remove_block(self.block_id);
+    }
+
+    fn description(&self) -> String {
+        format!("Create block {}", self.block.name())
+    }
+
+    fn memory_size(&self) -> usize {
+        mem::size_of_val(self)
+            + mem::size_of_val(self.block.shapes())
+            + mem::size_of_val(self.members.as_slice())
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::CreateBlock(self.clone())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddInstance {
+    block_id: BlockId,
+    block_name: String,
+    /// Shapes of the instance, in the order of the definition
+    shapes: Vec<(ShapeId, Shape)>,
+}
+
+pub fn add_instance(
+    block_id: BlockId,
+    block_name: String,
+    shapes: Vec<(ShapeId, Shape)>,
+) -> BoxedCommand {
+    Box::new(AddInstance {
+        block_id,
+        block_name,
+        shapes,
+    })
+}
+
+impl Command for AddInstance {
+    fn apply(&self, plan: &mut Plan) {
+        for (index, (shape_id, shape)) in self.shapes.iter().enumerate() {
+            plan.add_shape(*shape_id, shape.clone());
+            let member = BlockMember {
+                block_id: self.block_id,
+                index,
+            };
+            plan.set_block_member(*shape_id, member);
+        }
+    }
+
+    fn undo(&self, plan: &mut Plan) {
+        for (shape_id, _) in &self.shapes {
+            plan.remove_shape(*shape_id);
+        }
+    }
+
+    fn description(&self) -> String {
+        format!("Add {}", self.block_name)
+    }
+
+    fn memory_size(&self) -> usize {
+        mem::size_of_val(self) + mem::size_of_val(self.shapes.as_slice())
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::AddInstance(self.clone())
+    }
+}
+
+/// Change the definition of a block along with the shapes of all its instances
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditBlock {
+    block_id: BlockId,
+    old: Block,
+    new: Block,
+    /// Shapes of the instances with their old and new state
+    shapes: Vec<(ShapeId, Shape, Shape)>,
+}
+
+pub fn edit_block(
+    block_id: BlockId,
+    old: Block,
+    new: Block,
+    shapes: Vec<(ShapeId, Shape, Shape)>,
+) -> BoxedCommand {
+    Box::new(EditBlock {
+        block_id,
+        old,
+        new,
+        shapes,
+    })
+}
+
+impl Command for EditBlock {
+    fn apply(&self, plan: &mut Plan) {
+        plan.replace_block(self.block_id, self.new.clone());
+        for (shape_id, _, new) in &self.shapes {
+            plan.replace_shape(*shape_id, new.clone());
+        }
+    }
+
+    fn undo(&self, plan: &mut Plan) {
+        plan.replace_block(self.block_id, self.old.clone());
+        for (shape_id, old, _) in &self.shapes {
+            plan.replace_shape(*shape_id, old.clone());
+        }
+    }
+
+    fn description(&self) -> String {
+        format!("Edit block {}", self.old.name())
+    }
+
+    fn memory_size(&self) -> usize {
+        mem::size_of_val(self)
+            + mem::size_of_val(self.old.shapes())
+            + mem::size_of_val(self.new.shapes())
+            + mem::size_of_val(self.shapes.as_slice())
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::EditBlock(self.clone())
+    }
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::dimension::Dimension;
+use crate::model::plan::{DimensionId, Plan};
+
+use super::{BoxedCommand, Command, CommandRecord};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddDimension {
+    dimension_id: DimensionId,
+    dimension: Dimension,
+}
+
+pub fn add_dimension(dimension_id: DimensionId, dimension: Dimension) -> BoxedCommand {
+    Box::new(AddDimension {
+        dimension_id,
+        dimension,
+    })
+}
+
+impl Command for AddDimension {
+    fn apply(&self, plan: &mut Plan) {
+        plan.add_dimension(self.dimension_id, self.dimension.clone());
+    }
+
+    fn undo(&self, plan: &mut Plan) {
+        plan.remove_dimension(self.dimension_id);
+    }
+
+    fn description(&self) -> String {
+        format!("Add {} dimension", self.dimension.kind().name())
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::AddDimension(self.clone())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoveDimension {
+    dimension_id: DimensionId,
+    dimension: Dimension,
+}
+
+pub fn remove_dimension(dimension_id: DimensionId, dimension: Dimension) -> BoxedCommand {
+    Box::new(RemoveDimension {
+        dimension_id,
+        dimension,
+    })
+}
+
+impl Command for RemoveDimension {
+    fn apply(&self, plan: &mut Plan) {
+        plan.remove_dimension(self.dimension_id);
+    }
+
+    fn undo(&self, plan: &mut Plan) {
+        plan.add_dimension(self.dimension_id, self.dimension.clone());
+    }
+
+    fn description(&self) -> String {
+        format!("Remove {} dimension", self.dimension.kind().name())
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::RemoveDimension(self.clone())
+    }
+}
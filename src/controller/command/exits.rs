@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::plan::Plan;
+use crate::model::vec2::Vec2;
+
+use super::{BoxedCommand, Command, CommandRecord};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetExits {
+    old: Vec<Vec2>,
+    new: Vec<Vec2>,
+}
+
+pub fn set_exits(old: Vec<Vec2>, new: Vec<Vec2>) -> BoxedCommand {
+    Box::new(SetExits { old, new })
+}
+
+impl Command for SetExits {
+    fn apply(&self, plan: &mut Plan) {
+        plan.set_exits(self.new.clone());
+    }
+
+    fn undo(&self, plan: &mut Plan) {
+        plan.set_exits(self.old.clone());
+    }
+
+    fn description(&self) -> String {
+        if self.new.len() < self.old.len() {
+            "Remove exit".into()
+        } else {
+            "Add exit".into()
+        }
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + std::mem::size_of_val(self.old.as_slice())
+            + std::mem::size_of_val(self.new.as_slice())
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::SetExits(self.clone())
+    }
+}
@@ -0,0 +1,81 @@
+use std::mem;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::plan::{GroupId, Plan, ShapeId};
+
+use super::{BoxedCommand, Command, CommandRecord};
+
+/// Put shapes in a new group, taking them out of their previous group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Group {
+    group_id: GroupId,
+    /// Shapes of the group along with their previous group
+    shapes: Vec<(ShapeId, Option<GroupId>)>,
+}
+
+pub fn group(group_id: GroupId, shapes: Vec<(ShapeId, Option<GroupId>)>) -> BoxedCommand {
+    Box::new(Group { group_id, shapes })
+}
+
+impl Command for Group {
+    fn apply(&self, plan: &mut Plan) {
+        for (shape_id, _) in &self.shapes {
+            plan.set_group(*shape_id, Some(self.group_id));
+        }
+    }
+
+    fn undo(&self, plan: &mut Plan) {
+        for (shape_id, previous) in &self.shapes {
+            plan.set_group(*shape_id, *previous);
+        }
+    }
+
+    fn description(&self) -> String {
+        format!("Group {} shapes", self.shapes.len())
+    }
+
+    fn memory_size(&self) -> usize {
+        mem::size_of_val(self) + mem::size_of_val(self.shapes.as_slice())
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Group(self.clone())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ungroup {
+    /// Shapes taken out of their group
+    shapes: Vec<(ShapeId, GroupId)>,
+}
+
+pub fn ungroup(shapes: Vec<(ShapeId, GroupId)>) -> BoxedCommand {
+    Box::new(Ungroup { shapes })
+}
+
+impl Command for Ungroup {
+    fn apply(&self, plan: &mut Plan) {
+        for (shape_id, _) in &self.shapes {
+            plan.set_group(*shape_id, None);
+        }
+    }
+
+    fn undo(&self, plan: &mut Plan) {
+        for (shape_id, group_id) in &self.shapes {
+            plan.set_group(*shape_id, Some(*group_id));
+        }
+    }
+
+    fn description(&self) -> String {
+        format!("Ungroup {} shapes", self.shapes.len())
+    }
+
+    fn memory_size(&self) -> usize {
+        mem::size_of_val(self) + mem::size_of_val(self.shapes.as_slice())
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Ungroup(self.clone())
+    }
+}
@@ -0,0 +1,67 @@
+use std::mem;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::plan::{Plan, ShapeId};
+use crate::model::shape::Flip;
+
+use super::{BoxedCommand, Command, CommandRecord};
+
+/// Mirror shapes about a common axis. Mirroring twice about the same axis
+/// gives back the original shapes, so the command is its own undo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mirror {
+    flip: Flip,
+    /// Twice the coordinate of the axis
+    axis: i32,
+    shape_ids: Vec<ShapeId>,
+}
+
+pub fn mirror(flip: Flip, axis: i32, shape_ids: Vec<ShapeId>) -> BoxedCommand {
+    Box::new(Mirror {
+        flip,
+        axis,
+        shape_ids,
+    })
+}
+
+impl Mirror {
+    fn mirror_shapes(&self, plan: &mut Plan) {
+        for shape_id in &self.shape_ids {
+            if let Some(shape) = plan.get_shape(*shape_id) {
+                let mut shape = shape.clone();
+                shape.mirror(self.flip, self.axis);
+                plan.replace_shape(*shape_id, shape);
+            }
+        }
+    }
+}
+
+impl Command for Mirror {
+    fn apply(&self, plan: &mut Plan) {
+        self.mirror_shapes(plan);
+    }
+
+    fn undo(&self, plan: &mut Plan) {
+        self.mirror_shapes(plan);
+    }
+
+    fn description(&self) -> String {
+        let direction = match self.flip {
+            Flip::Horizontal => "horizontally",
+            Flip::Vertical => "vertically",
+        };
+        match self.shape_ids.len() {
+            1 => format!("Mirror shape {direction}"),
+            count => format!("Mirror {count} shapes {direction}"),
+        }
+    }
+
+    fn memory_size(&self) -> usize {
+        mem::size_of_val(self) + mem::size_of_val(self.shape_ids.as_slice())
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Mirror(self.clone())
+    }
+}
@@ -0,0 +1,84 @@
+use std::mem;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::block::BlockMember;
+use crate::model::metadata::Metadata;
+use crate::model::plan::{GroupId, Plan, ShapeId};
+use crate::model::shape::Shape;
+
+use super::{BoxedCommand, Command, CommandRecord};
+
+/// A removed shape with everything attached to it, restored on undo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RemovedShape {
+    shape_id: ShapeId,
+    shape: Shape,
+    metadata: Option<Metadata>,
+    block_member: Option<BlockMember>,
+    group_id: Option<GroupId>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoveShapes {
+    shapes: Vec<RemovedShape>,
+}
+
+/// Remove the shapes of the `plan` with the ids `shape_ids`,
+/// `None` if none of them is in the plan
+pub fn remove_shapes(plan: &Plan, shape_ids: &[ShapeId]) -> Option<BoxedCommand> {
+    let shapes: Vec<_> = shape_ids
+        .iter()
+        .filter_map(|shape_id| {
+            Some(RemovedShape {
+                shape_id: *shape_id,
+                shape: plan.get_shape(*shape_id)?.clone(),
+                metadata: plan.get_metadata(*shape_id).cloned(),
+                block_member: plan.get_block_member(*shape_id).copied(),
+                group_id: plan.get_group(*shape_id),
+            })
+        })
+        .collect();
+
+    if shapes.is_empty() {
+        return None;
+    }
+
+    Some(Box::new(RemoveShapes { shapes }))
+}
+
+impl Command for RemoveShapes {
+    fn apply(&self, plan: &mut Plan) {
+        for removed in &self.shapes {
+            plan.remove_shape(removed.shape_id);
+        }
+    }
+
+    fn undo(&self, plan: &mut Plan) {
+        for removed in &self.shapes {
+            plan.add_shape(removed.shape_id, removed.shape.clone());
+            if let Some(metadata) = &removed.metadata {
+                plan.set_metadata(removed.shape_id, metadata.clone());
+            }
+            if let Some(member) = removed.block_member {
+                plan.set_block_member(removed.shape_id, member);
+            }
+            plan.set_group(removed.shape_id, removed.group_id);
+        }
+    }
+
+    fn description(&self) -> String {
+        match self.shapes.as_slice() {
+            [removed] => format!("Remove {}", removed.shape.name().to_lowercase()),
+            shapes => format!("Remove {} shapes", shapes.len()),
+        }
+    }
+
+    fn memory_size(&self) -> usize {
+        mem::size_of_val(self) + mem::size_of_val(self.shapes.as_slice())
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::RemoveShapes(self.clone())
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::plan::Plan;
+use crate::model::room::Room;
+
+use super::{BoxedCommand, Command, CommandRecord};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetRoom {
+    old: Option<Room>,
+    new: Option<Room>,
+}
+
+pub fn set_room(old: Option<Room>, new: Option<Room>) -> BoxedCommand {
+    Box::new(SetRoom { old, new })
+}
+
+impl Command for SetRoom {
+    fn apply(&self, plan: &mut Plan) {
+        plan.set_room(self.new);
+    }
+
+    fn undo(&self, plan: &mut Plan) {
+        plan.set_room(self.old);
+    }
+
+    fn description(&self) -> String {
+        "Set room".into()
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::SetRoom(self.clone())
+    }
+}
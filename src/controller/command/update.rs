@@ -1,10 +1,14 @@
+use std::any::Any;
+
+use serde::{Deserialize, Serialize};
+
 use crate::model::plan::{ShapeId, Plan};
 use crate::model::shape::Shape;
 
-use super::{BoxedCommand, Command};
+use super::{BoxedCommand, Command, CommandRecord};
 
-#[derive(Debug)]
-struct UpdateShape {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateShape {
     new: Shape,
     old: Shape,
     shape_id: ShapeId,
@@ -22,4 +26,98 @@ impl Command for UpdateShape {
     fn undo(&self, plan: &mut Plan) {
         plan.replace_shape(self.shape_id, self.old.clone());
     }
+
+    fn description(&self) -> String {
+        format!("Update {}", self.old.name().to_lowercase())
+    }
+
+    /// Consecutive updates of the same shape are merged
+    fn coalesce(&mut self, next: &dyn Command) -> bool {
+        let next: &dyn Any = next;
+        match next.downcast_ref::<UpdateShape>() {
+            Some(next) if next.shape_id == self.shape_id => {
+                self.new = next.new.clone();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::UpdateShape(self.clone())
+    }
+}
+
+/// Update several shapes at once, like the members of a group being moved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateShapes {
+    /// Shapes with their old and new state
+    shapes: Vec<(ShapeId, Shape, Shape)>,
+    /// Description shown in the history instead of the default one,
+    /// for updates made by a tool, like the layout optimizer
+    #[serde(default)]
+    description: Option<String>,
+}
+
+pub fn update_shapes(
+    shapes: Vec<(ShapeId, Shape, Shape)>,
+    description: Option<String>,
+) -> BoxedCommand {
+    Box::new(UpdateShapes { shapes, description })
+}
+
+impl Command for UpdateShapes {
+    fn apply(&self, plan: &mut Plan) {
+        for (shape_id, _, new) in &self.shapes {
+            plan.replace_shape(*shape_id, new.clone());
+        }
+    }
+
+    fn undo(&self, plan: &mut Plan) {
+        for (shape_id, old, _) in &self.shapes {
+            plan.replace_shape(*shape_id, old.clone());
+        }
+    }
+
+    fn description(&self) -> String {
+        match &self.description {
+            Some(description) => description.clone(),
+            None => format!("Update {} shapes", self.shapes.len()),
+        }
+    }
+
+    /// Consecutive updates of the same shapes are merged, unless one of
+    /// them was made by a tool and stays a step of its own
+    fn coalesce(&mut self, next: &dyn Command) -> bool {
+        let next: &dyn Any = next;
+        let Some(next) = next.downcast_ref::<UpdateShapes>() else {
+            return false;
+        };
+
+        let same_shapes = self.description.is_none()
+            && next.description.is_none()
+            && self.shapes.len() == next.shapes.len()
+            && self
+                .shapes
+                .iter()
+                .zip(&next.shapes)
+                .all(|((id, _, _), (next_id, _, _))| id == next_id);
+        if same_shapes {
+            for ((_, _, new), (_, _, next_new)) in self.shapes.iter_mut().zip(&next.shapes) {
+                *new = next_new.clone();
+            }
+        }
+
+        same_shapes
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + std::mem::size_of_val(self.shapes.as_slice())
+            + self.description.as_ref().map_or(0, String::len)
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::UpdateShapes(self.clone())
+    }
 }
@@ -0,0 +1,153 @@
+//! Saving and loading of documents. A document holds the plan along
+//! with its whole history, so changes can still be undone after reopening it
+
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::plan::Plan;
+
+use super::history::History;
+
+#[derive(Serialize)]
+struct DocumentRef<'a> {
+    plan: &'a Plan,
+    history: &'a History,
+}
+
+#[derive(Deserialize)]
+struct Document {
+    plan: Plan,
+    history: History,
+}
+
+/// Access to the files requested by a message, made by the app once the
+/// message is handled, so replaying a journal touches no file
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileAction {
+    Save(PathBuf),
+    Open(PathBuf),
+    /// Write the report, as CSV, to the file
+    ExportReport(PathBuf, String),
+}
+
+#[derive(Debug)]
+pub enum DocumentError {
+    Io(io::Error),
+    Format(serde_json::Error),
+}
+
+impl fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocumentError::Io(err) => write!(f, "{err}"),
+            DocumentError::Format(err) => write!(f, "invalid document: {err}"),
+        }
+    }
+}
+
+impl From<io::Error> for DocumentError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for DocumentError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Format(value)
+    }
+}
+
+pub fn to_string(plan: &Plan, history: &History) -> Result<String, DocumentError> {
+    Ok(serde_json::to_string_pretty(&DocumentRef { plan, history })?)
+}
+
+pub fn from_str(content: &str) -> Result<(Plan, History), DocumentError> {
+    let Document { plan, history } = serde_json::from_str(content)?;
+    history
+        .validate()
+        .map_err(|err| serde::de::Error::custom(format!("invalid history: {err}")))
+        .map_err(DocumentError::Format)?;
+    Ok((plan, history))
+}
+
+pub fn save(path: &Path, plan: &Plan, history: &History) -> Result<(), DocumentError> {
+    fs::write(path, to_string(plan, history)?)?;
+    Ok(())
+}
+
+pub fn load(path: &Path) -> Result<(Plan, History), DocumentError> {
+    from_str(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::controller::command;
+    use crate::model::shape::{Circle, Rectangle};
+
+    #[test]
+    fn history_survives_round_trip() {
+        let mut plan = Plan::default();
+        let mut history = History::default();
+
+        let rect_id = plan.generate_shape_id();
+        let rect = Rectangle::new((0, 0).into(), 2, 2);
+        let command = command::add_shape(rect_id, rect.into());
+        history.push(command, &mut plan, Duration::ZERO);
+        let circle_id = plan.generate_shape_id();
+        let circle = Circle::new((10, 10).into(), 3);
+        let command = command::add_shape(circle_id, circle.into());
+        history.push(command, &mut plan, Duration::ZERO);
+        history.undo(&mut plan);
+
+        let content = to_string(&plan, &history).unwrap();
+        let (mut plan, mut history) = from_str(&content).unwrap();
+
+        assert_eq!(plan.iter().count(), 1);
+        assert_eq!(history.position(), 1);
+        assert!(history.can_redo());
+
+        history.redo(&mut plan);
+        assert!(plan.get_shape(circle_id).is_some());
+
+        history.jump_to(0, &mut plan);
+        assert_eq!(plan.iter().count(), 0);
+
+        // Ids generated after loading must not collide with the saved ones
+        let new_id = plan.generate_shape_id();
+        assert_ne!(new_id, rect_id);
+        assert_ne!(new_id, circle_id);
+    }
+
+    #[test]
+    fn invalid_document_is_rejected() {
+        assert!(matches!(from_str("{ \"plan\": 3 }"), Err(DocumentError::Format(_))));
+    }
+
+    #[test]
+    fn dangling_history_ids_are_rejected() {
+        let mut plan = Plan::default();
+        let mut history = History::default();
+        let id = plan.generate_shape_id();
+        let command = command::add_shape(id, Rectangle::new((0, 0).into(), 2, 2).into());
+        history.push(command, &mut plan, Duration::ZERO);
+        let content = to_string(&plan, &history).unwrap();
+        assert!(from_str(&content).is_ok());
+
+        let corruptions: [fn(&mut serde_json::Value); 3] = [
+            |history| history["current"] = 42.into(),
+            |history| history["nodes"]["0"]["children"] = serde_json::json!([1, 42]),
+            |history| history["nodes"]["0"]["active_child"] = 3.into(),
+        ];
+        for corrupt in corruptions {
+            let mut document: serde_json::Value = serde_json::from_str(&content).unwrap();
+            corrupt(&mut document["history"]);
+            let result = from_str(&document.to_string());
+            assert!(matches!(result, Err(DocumentError::Format(_))));
+        }
+    }
+}
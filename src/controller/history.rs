@@ -0,0 +1,541 @@
+//! Undo tree keeping every branch of the history. Doing a command after
+//! undoing others starts a new branch instead of discarding the undone
+//! commands, so no work is ever lost.
+//!
+//! Consecutive commands done in a short time window are merged into a
+//! single undo step when they allow it, and the oldest commands are
+//! forgotten when the history grows beyond the limits of its `HistoryConfig`.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::plan::Plan;
+
+use super::command::{BoxedCommand, Command};
+
+type NodeId = usize;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Node {
+    /// `None` for the root, which represents the initial state
+    command: Option<BoxedCommand>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    /// Index in `children` of the branch followed when redoing
+    active_child: usize,
+    /// Last time the command was done or merged with another one, in the
+    /// session clock. `None` for commands which can't be merged anymore
+    #[serde(skip)]
+    time: Option<Duration>,
+}
+
+impl Node {
+    fn new(command: Option<BoxedCommand>, parent: Option<NodeId>, time: Option<Duration>) -> Self {
+        Self {
+            command,
+            parent,
+            children: Vec::new(),
+            active_child: 0,
+            time,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HistoryConfig {
+    /// Commands done within this duration after the previous one
+    /// are merged with it when possible
+    pub coalesce_window: Duration,
+    /// Maximum number of commands kept in the history
+    pub max_len: usize,
+    /// Maximum memory used by the commands kept in the history, in bytes
+    pub max_memory: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            coalesce_window: Duration::from_millis(500),
+            max_len: 1000,
+            max_memory: 16 * 1024 * 1024,
+        }
+    }
+}
+
+impl HistoryConfig {
+    /// Read the config from the environment variables `PLACO_HISTORY_COALESCE_MS`,
+    /// `PLACO_HISTORY_MAX_LEN` and `PLACO_HISTORY_MAX_MEMORY`, using the default
+    /// value for the missing or invalid ones
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok()?.parse().ok()
+        }
+
+        let default = Self::default();
+        Self {
+            coalesce_window: var("PLACO_HISTORY_COALESCE_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(default.coalesce_window),
+            max_len: var("PLACO_HISTORY_MAX_LEN").unwrap_or(default.max_len),
+            max_memory: var("PLACO_HISTORY_MAX_MEMORY").unwrap_or(default.max_memory),
+        }
+    }
+}
+
+/// A step of the history as displayed to the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub description: String,
+    /// Index of the branch of this entry among its siblings
+    pub branch: usize,
+    pub branch_count: usize,
+}
+
+/// The whole tree is serialized, so saved documents keep their history
+#[derive(Debug, Serialize, Deserialize)]
+pub struct History {
+    nodes: HashMap<NodeId, Node>,
+    next_id: NodeId,
+    root: NodeId,
+    current: NodeId,
+    #[serde(skip)]
+    config: HistoryConfig,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl History {
+    pub fn new(config: HistoryConfig) -> Self {
+        let root = 0;
+        Self {
+            nodes: HashMap::from([(root, Node::new(None, None, None))]),
+            next_id: root + 1,
+            root,
+            current: root,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> HistoryConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: HistoryConfig) {
+        self.config = config;
+    }
+
+    /// Check the structure of a deserialized tree, which the other methods
+    /// rely on: every referenced node exists, the links between parents and
+    /// children match, and the current state can be reached from the root
+    pub fn validate(&self) -> Result<(), String> {
+        let root = self.nodes.get(&self.root).ok_or("missing root")?;
+        if root.parent.is_some() || root.command.is_some() {
+            return Err("the root has a parent or a command".into());
+        }
+
+        for (id, node) in &self.nodes {
+            if *id >= self.next_id {
+                return Err(format!("node {id} is after the next id"));
+            }
+            if *id != self.root && node.command.is_none() {
+                return Err(format!("node {id} has no command"));
+            }
+            if let Some(parent) = node.parent {
+                let parent_node = self.nodes.get(&parent).ok_or(format!("missing node {parent}"))?;
+                if !parent_node.children.contains(id) {
+                    return Err(format!("node {id} is not a child of its parent"));
+                }
+            }
+            for child in &node.children {
+                let child_node = self.nodes.get(child).ok_or(format!("missing node {child}"))?;
+                if child_node.parent != Some(*id) {
+                    return Err(format!("node {child} is not a child of node {id}"));
+                }
+            }
+            if !node.children.is_empty() && node.active_child >= node.children.len() {
+                return Err(format!("active branch of node {id} out of range"));
+            }
+        }
+
+        // Parents are checked above, so the walk stays among existing nodes,
+        // and it can't take more steps than there are nodes without a cycle
+        let mut id = self.current;
+        for _ in 0..self.nodes.len() {
+            let node = self.nodes.get(&id).ok_or(format!("missing node {id}"))?;
+            match node.parent {
+                _ if id == self.root => return Ok(()),
+                Some(parent) => id = parent,
+                None => break,
+            }
+        }
+        Err("the current state can't be reached from the root".into())
+    }
+
+    fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[&id]
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        self.nodes.get_mut(&id).unwrap()
+    }
+
+    fn command(&self, id: NodeId) -> &BoxedCommand {
+        self.node(id)
+            .command
+            .as_ref()
+            .expect("only the root has no command")
+    }
+
+    fn active_child(&self, id: NodeId) -> Option<NodeId> {
+        let node = self.node(id);
+        node.children.get(node.active_child).copied()
+    }
+
+    /// Apply the `command` done at `time` and add it as a new branch after
+    /// the current state, or merge it with the last command
+    pub fn push(&mut self, command: BoxedCommand, plan: &mut Plan, time: Duration) {
+        command.apply(plan);
+
+        if !self.coalesce(command.as_ref(), time) {
+            self.insert(command, time);
+            self.evict();
+        }
+    }
+
+    /// Merge the `command` into the current one if it was done recently and
+    /// nothing was undone since then, the merged command would break the
+    /// branches after it otherwise
+    fn coalesce(&mut self, command: &dyn Command, time: Duration) -> bool {
+        let window = self.config.coalesce_window;
+        let current = self.node_mut(self.current);
+
+        let recent = current
+            .time
+            .is_some_and(|current_time| time.saturating_sub(current_time) < window);
+        if !recent || !current.children.is_empty() {
+            return false;
+        }
+
+        let coalesced = current
+            .command
+            .as_mut()
+            .is_some_and(|current_command| current_command.coalesce(command));
+        if coalesced {
+            current.time = Some(time);
+        }
+
+        coalesced
+    }
+
+    fn insert(&mut self, command: BoxedCommand, time: Duration) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.nodes.insert(id, Node::new(Some(command), Some(self.current), Some(time)));
+
+        let parent = self.node_mut(self.current);
+        parent.children.push(id);
+        parent.active_child = parent.children.len() - 1;
+
+        self.current = id;
+    }
+
+    /// Forget the oldest commands while the history is over its limits.
+    /// The first command on the path to the current state becomes part of
+    /// the initial state and the branches starting before it are dropped
+    fn evict(&mut self) {
+        while self.current != self.root && self.over_limits() {
+            let kept_child = self.ancestor_child_of_root();
+
+            let old_root = self.nodes.remove(&self.root).unwrap();
+            for child in old_root.children.into_iter().filter(|child| *child != kept_child) {
+                self.remove_subtree(child);
+            }
+
+            let new_root = self.node_mut(kept_child);
+            new_root.command = None;
+            new_root.parent = None;
+            self.root = kept_child;
+        }
+    }
+
+    fn over_limits(&self) -> bool {
+        let len = self.nodes.len() - 1;
+        let memory: usize = self
+            .nodes
+            .values()
+            .filter_map(|node| node.command.as_ref())
+            .map(|command| command.memory_size())
+            .sum();
+
+        len > self.config.max_len || memory > self.config.max_memory
+    }
+
+    /// The child of the root leading to the current state
+    fn ancestor_child_of_root(&self) -> NodeId {
+        std::iter::successors(Some(self.current), |id| self.node(*id).parent)
+            .find(|id| self.node(*id).parent == Some(self.root))
+            .expect("the current state is not the root")
+    }
+
+    fn remove_subtree(&mut self, id: NodeId) {
+        if let Some(node) = self.nodes.remove(&id) {
+            for child in node.children {
+                self.remove_subtree(child);
+            }
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.node(self.current).parent.is_some()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.active_child(self.current).is_some()
+    }
+
+    pub fn undo(&mut self, plan: &mut Plan) {
+        if let Some(parent) = self.node(self.current).parent {
+            self.command(self.current).undo(plan);
+            self.current = parent;
+        }
+    }
+
+    /// Redo the command of the last visited branch after the current state
+    pub fn redo(&mut self, plan: &mut Plan) {
+        if let Some(child) = self.active_child(self.current) {
+            self.command(child).apply(plan);
+            self.current = child;
+        }
+    }
+
+    /// Check if the current state has sibling branches to switch to
+    pub fn can_switch_branch(&self) -> bool {
+        self.node(self.current)
+            .parent
+            .is_some_and(|parent| self.node(parent).children.len() > 1)
+    }
+
+    /// Replace the last command by the one of the next (or previous)
+    /// sibling branch
+    pub fn switch_branch(&mut self, forward: bool, plan: &mut Plan) {
+        if !self.can_switch_branch() {
+            return;
+        }
+
+        self.undo(plan);
+
+        let parent = self.node_mut(self.current);
+        let count = parent.children.len();
+        parent.active_child = if forward {
+            (parent.active_child + 1) % count
+        } else {
+            (parent.active_child + count - 1) % count
+        };
+
+        self.redo(plan);
+    }
+
+    /// Number of commands done to reach the current state
+    pub fn position(&self) -> usize {
+        std::iter::successors(self.node(self.current).parent, |id| self.node(*id).parent).count()
+    }
+
+    /// Undo or redo commands along the displayed path until `position`
+    /// commands are done
+    pub fn jump_to(&mut self, position: usize, plan: &mut Plan) {
+        let mut current_position = self.position();
+
+        while current_position > position {
+            self.undo(plan);
+            current_position -= 1;
+        }
+        while current_position < position && self.can_redo() {
+            self.redo(plan);
+            current_position += 1;
+        }
+    }
+
+    /// The commands from the initial state to the current state, followed by
+    /// the commands that would be redone from the current state
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        let mut path: Vec<NodeId> =
+            std::iter::successors(Some(self.current), |id| self.node(*id).parent).collect();
+        path.pop(); // The root has no command
+        path.reverse();
+        path.extend(std::iter::successors(
+            self.active_child(self.current),
+            |id| self.active_child(*id),
+        ));
+
+        path.into_iter()
+            .map(|id| {
+                let siblings = &self.node(self.node(id).parent.unwrap()).children;
+                HistoryEntry {
+                    description: self.command(id).description(),
+                    branch: siblings.iter().position(|sibling| *sibling == id).unwrap(),
+                    branch_count: siblings.len(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::controller::command;
+    use crate::model::plan::ShapeId;
+    use crate::model::shape::{Rectangle, Shape};
+
+    fn add_rectangle(plan: &mut Plan, x: i32) -> BoxedCommand {
+        let id = plan.generate_shape_id();
+        command::add_shape(id, Rectangle::new((x, 0).into(), 1, 1).into())
+    }
+
+    fn shape_positions(plan: &Plan) -> Vec<i32> {
+        plan.iter()
+            .map(|(_, shape)| match shape {
+                Shape::Rectangle(rect) => rect.pos().x,
+                Shape::Circle(circle) => circle.pos().x,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn new_command_after_undo_keeps_branch() {
+        let mut plan = Plan::default();
+        let mut history = History::default();
+
+        let command = add_rectangle(&mut plan, 0);
+        history.push(command, &mut plan, Duration::ZERO);
+        let command = add_rectangle(&mut plan, 1);
+        history.push(command, &mut plan, Duration::ZERO);
+        history.undo(&mut plan);
+        let command = add_rectangle(&mut plan, 2);
+        history.push(command, &mut plan, Duration::ZERO);
+
+        assert_eq!(shape_positions(&plan), [0, 2]);
+        assert!(history.can_switch_branch());
+
+        history.switch_branch(false, &mut plan);
+        assert_eq!(shape_positions(&plan), [0, 1]);
+        assert_eq!(history.entries()[1].branch, 0);
+        assert_eq!(history.entries()[1].branch_count, 2);
+
+        history.switch_branch(false, &mut plan);
+        assert_eq!(shape_positions(&plan), [0, 2]);
+    }
+
+    #[test]
+    fn redo_follows_last_visited_branch() {
+        let mut plan = Plan::default();
+        let mut history = History::default();
+
+        let command = add_rectangle(&mut plan, 0);
+        history.push(command, &mut plan, Duration::ZERO);
+        history.undo(&mut plan);
+        let command = add_rectangle(&mut plan, 1);
+        history.push(command, &mut plan, Duration::ZERO);
+        let command = add_rectangle(&mut plan, 2);
+        history.push(command, &mut plan, Duration::ZERO);
+
+        history.jump_to(0, &mut plan);
+        assert!(plan.iter().next().is_none());
+        assert_eq!(history.entries().len(), 2);
+
+        history.jump_to(2, &mut plan);
+        assert_eq!(shape_positions(&plan), [1, 2]);
+        assert_eq!(history.position(), 2);
+        assert!(!history.can_redo());
+    }
+
+    fn update_rectangle(plan: &Plan, id: ShapeId, x: i32) -> BoxedCommand {
+        let old = plan.get_shape(id).unwrap().clone();
+        command::update_shape(id, old, Rectangle::new((x, 0).into(), 1, 1).into())
+    }
+
+    #[test]
+    fn consecutive_updates_are_coalesced() {
+        let mut plan = Plan::default();
+        let mut history = History::new(HistoryConfig {
+            coalesce_window: Duration::from_secs(3600),
+            ..Default::default()
+        });
+
+        let id = plan.generate_shape_id();
+        let command = command::add_shape(id, Rectangle::new((0, 0).into(), 1, 1).into());
+        history.push(command, &mut plan, Duration::ZERO);
+        for x in 1..5 {
+            let command = update_rectangle(&plan, id, x);
+            history.push(command, &mut plan, Duration::ZERO);
+        }
+
+        assert_eq!(history.position(), 2);
+        assert_eq!(shape_positions(&plan), [4]);
+
+        history.undo(&mut plan);
+        assert_eq!(shape_positions(&plan), [0]);
+    }
+
+    #[test]
+    fn updates_outside_window_are_not_coalesced() {
+        let mut plan = Plan::default();
+        let mut history = History::new(HistoryConfig {
+            coalesce_window: Duration::from_millis(500),
+            ..Default::default()
+        });
+
+        let id = plan.generate_shape_id();
+        let command = command::add_shape(id, Rectangle::new((0, 0).into(), 1, 1).into());
+        history.push(command, &mut plan, Duration::ZERO);
+        for x in 1..5 {
+            let command = update_rectangle(&plan, id, x);
+            history.push(command, &mut plan, Duration::from_secs(x as u64));
+        }
+
+        assert_eq!(history.position(), 5);
+    }
+
+    #[test]
+    fn oldest_commands_are_evicted() {
+        let mut plan = Plan::default();
+        let mut history = History::new(HistoryConfig {
+            max_len: 3,
+            ..Default::default()
+        });
+
+        for x in 0..5 {
+            let command = add_rectangle(&mut plan, x);
+            history.push(command, &mut plan, Duration::ZERO);
+        }
+
+        assert_eq!(history.position(), 3);
+        history.jump_to(0, &mut plan);
+        assert_eq!(shape_positions(&plan), [0, 1]);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn eviction_is_bounded_by_memory() {
+        let mut plan = Plan::default();
+        let command = add_rectangle(&mut plan, 0);
+        let mut history = History::new(HistoryConfig {
+            max_memory: 2 * command.memory_size(),
+            ..Default::default()
+        });
+
+        history.push(command, &mut plan, Duration::ZERO);
+        for x in 1..5 {
+            let command = add_rectangle(&mut plan, x);
+            history.push(command, &mut plan, Duration::ZERO);
+        }
+
+        assert_eq!(history.position(), 2);
+    }
+}
@@ -0,0 +1,184 @@
+//! Journal of the messages received by the `Controller`, stored as one
+//! JSON entry per line. Replaying a journal in a fresh controller
+//! reproduces the exact same plan, which helps to reproduce bug reports.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::message::Message;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Time at which the message was received, since the start of the session
+    pub time: Duration,
+    pub message: Message,
+}
+
+#[derive(Debug)]
+pub struct JournalWriter {
+    writer: BufWriter<File>,
+    /// Last cursor move not written yet
+    cursor_move: Option<JournalEntry>,
+}
+
+impl JournalWriter {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            cursor_move: None,
+        })
+    }
+
+    /// Write the entry and flush it right away, so the journal is
+    /// complete even if the application crashes.
+    ///
+    /// Cursor moves are only written before the next other message, as the
+    /// replay only needs the position of the cursor when pasting
+    pub fn record(&mut self, entry: &JournalEntry) -> io::Result<()> {
+        if matches!(entry.message, Message::CursorMoved(_)) {
+            self.cursor_move = Some(entry.clone());
+            return Ok(());
+        }
+
+        if let Some(cursor_move) = self.cursor_move.take() {
+            self.write(&cursor_move)?;
+        }
+        self.write(entry)?;
+        self.writer.flush()
+    }
+
+    fn write(&mut self, entry: &JournalEntry) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, entry)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+pub fn read(path: &Path) -> io::Result<Vec<JournalEntry>> {
+    parse(BufReader::new(File::open(path)?))
+}
+
+pub fn parse(reader: impl BufRead) -> io::Result<Vec<JournalEntry>> {
+    reader
+        .lines()
+        .filter(|line| !line.as_ref().is_ok_and(|line| line.trim().is_empty()))
+        .map(|line| Ok(serde_json::from_str(&line?)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::controller::history::HistoryConfig;
+    use crate::controller::Controller;
+    use crate::model::shape::{Circle, Rectangle};
+
+    #[test]
+    fn replay_reproduces_plan() {
+        let path = std::env::temp_dir().join(format!("placo-journal-{}.jsonl", std::process::id()));
+
+        let mut controller = Controller::default();
+        controller.record(JournalWriter::create(&path).unwrap());
+
+        let messages = [
+            Message::AddShape(Rectangle::new((0, 0).into(), 4, 2).into()),
+            Message::CursorMoved(Some((3, 3).into())),
+            Message::CursorMoved(Some((5, 5).into())),
+            Message::AddShape(Circle::new((10, 10).into(), 3).into()),
+            Message::MoveButton,
+        ];
+        for (i, message) in messages.into_iter().enumerate() {
+            controller.update_at(Duration::from_secs(i as u64), message);
+        }
+        let (id, shape) = controller.plan().iter().next().unwrap().clone();
+        let moves = [(20, 0), (30, 0)];
+        for (i, pos) in moves.into_iter().enumerate() {
+            let mut shape = shape.clone();
+            shape.move_to(pos.into());
+            let time = Duration::from_secs(10) + Duration::from_millis(100 * i as u64);
+            controller.update_at(time, Message::UpdateShape(id, shape));
+        }
+        // Both moves are merged, undo cancels them together
+        controller.update_at(Duration::from_secs(11), Message::Undo);
+
+        let entries = read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // Only the last position of the cursor is kept
+        let cursor_moves = entries
+            .iter()
+            .filter(|entry| matches!(entry.message, Message::CursorMoved(_)))
+            .count();
+        assert_eq!(cursor_moves, 1);
+        let mut replayed = Controller::new(HistoryConfig::default());
+        replayed.replay(entries);
+
+        assert_eq!(replayed.plan(), controller.plan());
+        assert_eq!(replayed.history(), controller.history());
+        assert_eq!(replayed.plan().get_shape(id), Some(&shape));
+        assert_eq!(replayed.cursor(), Some((5, 5).into()));
+    }
+
+    #[test]
+    fn clock_resumes_after_replay() {
+        let mut recorded = Controller::default();
+        let shape = Rectangle::new((0, 0).into(), 4, 2);
+        recorded.update_at(Duration::ZERO, Message::AddShape(shape.clone().into()));
+        let (id, _) = recorded.plan().iter().next().unwrap().clone();
+        let moved = Rectangle::new((10, 0).into(), 4, 2);
+        let entries = [
+            JournalEntry {
+                time: Duration::ZERO,
+                message: Message::AddShape(shape.into()),
+            },
+            JournalEntry {
+                time: Duration::from_secs(3600),
+                message: Message::UpdateShape(id, moved.into()),
+            },
+            JournalEntry {
+                time: Duration::from_secs(3601),
+                message: Message::ScaleUp,
+            },
+        ];
+
+        let mut controller = Controller::new(HistoryConfig {
+            coalesce_window: Duration::from_millis(50),
+            ..Default::default()
+        });
+        controller.replay(entries);
+        assert_eq!(controller.history().len(), 2);
+
+        // Live moves are neither merged with the replayed one nor together
+        controller.update(Message::UpdateShape(id, Rectangle::new((20, 0).into(), 4, 2).into()));
+        std::thread::sleep(Duration::from_millis(60));
+        controller.update(Message::UpdateShape(id, Rectangle::new((30, 0).into(), 4, 2).into()));
+        assert_eq!(controller.history().len(), 4);
+    }
+
+    #[test]
+    fn replay_touches_no_file() {
+        for message in [Message::SaveButton, Message::OpenButton, Message::ExportReportButton] {
+            let mut controller = Controller::default();
+            controller.update(Message::FilePathChanged("replayed.json".into()));
+            controller.update_at(Duration::ZERO, message.clone());
+            assert!(controller.take_file_action().is_some());
+
+            controller.replay([JournalEntry {
+                time: Duration::ZERO,
+                message,
+            }]);
+            assert!(controller.take_file_action().is_none());
+        }
+    }
+
+    #[test]
+    fn parse_skips_blank_lines() {
+        let journal = "{\"time\":{\"secs\":0,\"nanos\":0},\"message\":\"Undo\"}\n\n";
+        let entries = parse(journal.as_bytes()).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert!(matches!(entries[0].message, Message::Undo));
+    }
+}
@@ -1,9 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::access::Agent;
+use crate::model::array::ArrayLayout;
+use crate::model::block::Block;
+use crate::model::optimize::Settings;
+use crate::model::catalog::CatalogEntry;
+use crate::model::plan::{BlockId, ShapeId};
+use crate::model::shape::{Circle, Flip, Rectangle, Shape};
+use crate::model::vec2::Vec2;
+
 #[derive(Debug, Default, Clone)]
 pub enum ControllerState {
     #[default]
     Idle,
     AddingShape(ShapeType),
     MovingShapes,
+    Measuring(MeasureKind),
+    Annotating,
+    PlacingItem(CatalogEntry),
+    SelectingArea(AreaAction),
+    PlacingBlock(BlockId),
+    /// Mirroring the selection about an axis chosen on the plan
+    Mirroring(Flip),
+    /// Looking for paths moving the selected shape to spots chosen on the plan
+    PlanningMotion { rotation: bool },
+    /// Showing which shapes can be reached from an entrance chosen on the plan
+    AnalyzingAccess,
+    /// Showing the walking distance to the exits, marked on the plan
+    AnalyzingEgress,
+    /// Showing the floor covered by the circles of a category
+    AnalyzingCoverage,
+}
+
+/// What is done with the shapes of an area selected on the plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AreaAction {
+    /// Make a block of the shapes which are not part of a block yet
+    CreateBlock,
+    /// Put the shapes in a single group
+    Group,
+    /// Take the shapes out of their groups
+    Ungroup,
+    /// Select the shapes
+    Select,
+    /// Use the area as the room of the plan
+    SetRoom,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -11,3 +52,276 @@ pub enum ShapeType {
     Circle,
     Rectangle
 }
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum MeasureKind {
+    /// Distance between two points
+    Distance,
+    /// Length of a path going through several points
+    Polyline,
+    /// Area of the polygon defined by several points
+    Area,
+    /// Nearest distance between two shapes
+    ShapeDistance,
+}
+
+/// Dimensions of the shapes of a block being edited, as typed by the user.
+/// Each shape has its width and height, or its radius
+#[derive(Debug, Clone)]
+pub struct BlockDraft {
+    block_id: BlockId,
+    fields: Vec<Vec<String>>,
+}
+
+impl BlockDraft {
+    pub fn new(block_id: BlockId, block: &Block) -> Self {
+        let fields = block
+            .shapes()
+            .iter()
+            .map(|shape| match shape {
+                Shape::Circle(circle) => vec![circle.radius().to_string()],
+                Shape::Rectangle(rect) => vec![rect.width().to_string(), rect.height().to_string()],
+            })
+            .collect();
+
+        Self { block_id, fields }
+    }
+
+    pub fn block_id(&self) -> BlockId {
+        self.block_id
+    }
+
+    pub fn fields(&self) -> &[Vec<String>] {
+        &self.fields
+    }
+
+    pub fn set_field(&mut self, shape: usize, field: usize, value: String) {
+        if let Some(old) = self.fields.get_mut(shape).and_then(|fields| fields.get_mut(field)) {
+            *old = value;
+        }
+    }
+
+    /// Definition of the `block` with the dimensions of the draft,
+    /// `None` if one of them is not a positive number
+    pub fn apply(&self, block: &Block) -> Option<Block> {
+        let shapes = block
+            .shapes()
+            .iter()
+            .zip(&self.fields)
+            .map(|(shape, fields)| {
+                let values = fields
+                    .iter()
+                    .map(|field| field.trim().parse().ok().filter(|value| *value > 0))
+                    .collect::<Option<Vec<u32>>>()?;
+
+                match (shape, values.as_slice()) {
+                    (Shape::Circle(circle), [radius]) => {
+                        Some(Circle::new(circle.pos(), *radius).into())
+                    }
+                    (Shape::Rectangle(rect), [width, height]) => {
+                        Some(Rectangle::new(rect.pos(), *width, *height).into())
+                    }
+                    _ => None,
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(block.with_shapes(shapes))
+    }
+}
+
+/// Largest number of cells of an array, to keep the preview responsive
+const MAX_ARRAY_CELLS: u32 = 1000;
+
+/// Parameters of an array of the selection, as typed by the user
+#[derive(Debug, Clone)]
+pub struct ArrayDraft {
+    /// Columns, rows, horizontal gap and vertical gap
+    fields: [String; 4],
+}
+
+impl Default for ArrayDraft {
+    fn default() -> Self {
+        Self {
+            fields: ["3", "1", "1", "1"].map(String::from),
+        }
+    }
+}
+
+impl ArrayDraft {
+    pub const FIELD_NAMES: [&'static str; 4] = ["Columns", "Rows", "Gap x", "Gap y"];
+
+    pub fn fields(&self) -> &[String; 4] {
+        &self.fields
+    }
+
+    pub fn set_field(&mut self, field: usize, value: String) {
+        if let Some(old) = self.fields.get_mut(field) {
+            *old = value;
+        }
+    }
+
+    /// Layout of the array, `None` if a count is zero or not a whole number,
+    /// a gap is negative or the array is too large. Negative counts lay
+    /// the columns out to the left and the rows up
+    pub fn layout(&self) -> Option<ArrayLayout> {
+        let [columns, rows, gap_x, gap_y] = &self.fields;
+        let count = |field: &String| field.trim().parse::<i32>().ok().filter(|count| *count != 0);
+        let gap = |field: &String| field.trim().parse().ok().filter(|gap| *gap >= 0);
+        let (columns, rows) = (count(columns)?, count(rows)?);
+
+        let layout = ArrayLayout {
+            columns: columns.unsigned_abs(),
+            rows: rows.unsigned_abs(),
+            gap: Vec2::new(gap(gap_x)?, gap(gap_y)?),
+            direction: Vec2::new(columns.signum(), rows.signum()),
+        };
+
+        (layout.columns.checked_mul(layout.rows)? <= MAX_ARRAY_CELLS).then_some(layout)
+    }
+}
+
+/// Number of items of each entry of the catalog to pack in the room,
+/// as typed by the user
+#[derive(Debug, Clone, Default)]
+pub struct PackingDraft {
+    quantities: Vec<String>,
+}
+
+impl PackingDraft {
+    /// Largest total number of items to pack, as each item is checked
+    /// against every spot left by the items placed before it
+    pub const MAX_ITEMS: usize = 500;
+
+    pub fn quantity(&self, entry: usize) -> &str {
+        self.quantities.get(entry).map_or("", String::as_str)
+    }
+
+    pub fn set_quantity(&mut self, entry: usize, value: String) {
+        if self.quantities.len() <= entry {
+            self.quantities.resize(entry + 1, String::new());
+        }
+        self.quantities[entry] = value;
+    }
+
+    /// Quantity of each of the `entries` of the catalog, an empty field
+    /// meaning none. `None` if a quantity isn't a whole number or there
+    /// are more than `MAX_ITEMS` items in total
+    pub fn counts(&self, entries: usize) -> Option<Vec<usize>> {
+        let counts: Vec<usize> = (0..entries)
+            .map(|entry| match self.quantity(entry).trim() {
+                "" => Some(0),
+                quantity => quantity.parse().ok(),
+            })
+            .collect::<Option<_>>()?;
+
+        let total = counts.iter().try_fold(0usize, |total, count| total.checked_add(*count))?;
+        (total <= Self::MAX_ITEMS).then_some(counts)
+    }
+}
+
+/// Parameters of the layout optimizer, as typed by the user
+#[derive(Debug, Clone)]
+pub struct OptimizerDraft {
+    /// Seed, iterations and the weights of the spacing, walls and category costs
+    fields: [String; 5],
+}
+
+impl Default for OptimizerDraft {
+    fn default() -> Self {
+        Self {
+            fields: ["1", "2000", "1", "0", "0"].map(String::from),
+        }
+    }
+}
+
+impl OptimizerDraft {
+    pub const FIELD_NAMES: [&'static str; 5] =
+        ["Seed", "Iterations", "Spacing", "Walls", "Category"];
+    /// Largest number of iterations. The optimizer runs on the UI thread and
+    /// scores the whole layout at each iteration, which takes around 0.1 ms
+    /// for 100 shapes, so the UI freezes for about a second at most
+    pub const MAX_ITERATIONS: u32 = 10_000;
+
+    pub fn fields(&self) -> &[String; 5] {
+        &self.fields
+    }
+
+    pub fn set_field(&mut self, field: usize, value: String) {
+        if let Some(old) = self.fields.get_mut(field) {
+            *old = value;
+        }
+    }
+
+    /// Settings of the optimizer and weights of the costs, `None` if a field
+    /// isn't a number, a weight is negative or there are too many iterations
+    pub fn parse(&self) -> Option<(Settings, [f64; 3])> {
+        let [seed, iterations, weights @ ..] = &self.fields;
+        let settings = Settings {
+            seed: seed.trim().parse().ok()?,
+            iterations: iterations
+                .trim()
+                .parse()
+                .ok()
+                .filter(|iterations| *iterations <= Self::MAX_ITERATIONS)?,
+        };
+
+        let mut parsed = [0.0; 3];
+        for (weight, field) in parsed.iter_mut().zip(weights) {
+            *weight = field.trim().parse().ok().filter(|weight: &f64| *weight >= 0.0)?;
+        }
+
+        Some((settings, parsed))
+    }
+}
+
+/// Layout proposed by the optimizer, waiting to be applied
+#[derive(Debug, Clone)]
+pub struct OptimizationPreview {
+    /// Moved shapes with their state before and after the optimization
+    pub shapes: Vec<(ShapeId, Shape, Shape)>,
+    pub initial_cost: f64,
+    pub cost: f64,
+}
+
+/// Size of the agent of the accessibility analysis, as typed by the user
+#[derive(Debug, Clone)]
+pub struct AccessDraft {
+    /// Width and turning diameter
+    fields: [String; 2],
+}
+
+impl Default for AccessDraft {
+    fn default() -> Self {
+        Self {
+            fields: ["5", "8"].map(String::from),
+        }
+    }
+}
+
+impl AccessDraft {
+    pub const FIELD_NAMES: [&'static str; 2] = ["Width", "Turning"];
+
+    pub fn fields(&self) -> &[String; 2] {
+        &self.fields
+    }
+
+    pub fn set_field(&mut self, field: usize, value: String) {
+        if let Some(old) = self.fields.get_mut(field) {
+            *old = value;
+        }
+    }
+
+    /// Agent of the analysis, `None` if a size isn't a positive number
+    pub fn agent(&self) -> Option<Agent> {
+        let [width, turning] = self
+            .fields
+            .each_ref()
+            .map(|field| field.trim().parse().ok().filter(|size: &f32| *size > 0.0));
+
+        Some(Agent {
+            width: width?,
+            turning: turning?,
+        })
+    }
+}
@@ -1,4 +1,11 @@
+use std::path::Path;
+use std::process::ExitCode;
+
+use app::Flags;
+use controller::history::HistoryConfig;
+use controller::journal::{self, JournalWriter};
 use iced::{Application, Settings};
+use model::catalog::Catalog;
 
 mod app;
 mod message;
@@ -7,6 +14,60 @@ mod model;
 mod controller;
 mod utils;
 
-fn main() {
-    app::App::run(Settings::default()).unwrap();
+const USAGE: &str = "Usage: placo [--record <journal>] [--replay <journal>] [--catalog <catalog>]";
+
+/// Catalog loaded when it exists and no other catalog is given
+const DEFAULT_CATALOG_PATH: &str = "catalog.json";
+
+fn main() -> ExitCode {
+    let flags = match parse_args(std::env::args().skip(1)) {
+        Ok(flags) => flags,
+        Err(err) => {
+            eprintln!("{err}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    app::App::run(Settings::with_flags(flags)).unwrap();
+    ExitCode::SUCCESS
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Flags, String> {
+    let mut flags = Flags {
+        history_config: HistoryConfig::from_env(),
+        ..Default::default()
+    };
+    let mut catalog_path = None;
+
+    while let Some(arg) = args.next() {
+        let path = args.next().ok_or_else(|| format!("Missing path after {arg}"))?;
+        let path = Path::new(&path);
+
+        match arg.as_str() {
+            "--record" => {
+                let journal = JournalWriter::create(path)
+                    .map_err(|err| format!("Can't create {}: {err}", path.display()))?;
+                flags.journal = Some(journal);
+            }
+            "--replay" => {
+                flags.replay = journal::read(path)
+                    .map_err(|err| format!("Can't read {}: {err}", path.display()))?;
+            }
+            "--catalog" => catalog_path = Some(path.to_owned()),
+            _ => return Err(format!("Unknown argument {arg}")),
+        }
+    }
+
+    let default_catalog_path = Path::new(DEFAULT_CATALOG_PATH);
+    let catalog_path = catalog_path.or_else(|| {
+        default_catalog_path
+            .exists()
+            .then(|| default_catalog_path.to_owned())
+    });
+    if let Some(path) = catalog_path {
+        flags.catalog = Catalog::load(&path)
+            .map_err(|err| format!("Can't load {}: {err}", path.display()))?;
+    }
+
+    Ok(flags)
 }
@@ -1,16 +1,115 @@
-use crate::model::plan::ShapeId;
-use crate::model::shape::Shape;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+use crate::controller::state::MeasureKind;
+use crate::model::dimension::Dimension;
+use crate::model::metadata::Metadata;
+use crate::model::plan::{BlockId, DimensionId, ShapeId};
+use crate::model::shape::{Flip, Shape};
+use crate::model::vec2::Vec2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Message {
     AddRectangleButton,
     AddCircleButton,
     MoveButton,
+    MeasureButton(MeasureKind),
+    DimensionButton,
+    /// Start placing the entry of the catalog at the given index
+    CatalogButton(usize),
     AddShape(Shape),
+    /// Add a shape along with the metadata describing it
+    AddItem(Shape, Metadata),
+    CreateBlockButton,
+    GroupButton,
+    UngroupButton,
+    /// An area was selected between the two corners, its shapes are used
+    /// by the action of the current mode
+    AreaSelected(Vec2, Vec2),
+    /// Replace the selection by the given shapes
+    Select(Vec<ShapeId>),
+    SelectButton,
+    CopyButton,
+    CutButton,
+    PasteButton,
+    DuplicateButton,
+    DeleteButton,
+    /// Content of the clipboard to paste, read after `PasteButton`
+    Paste(Option<String>),
+    ArrayButton,
+    /// Change a parameter of the array being prepared
+    ArrayDraftChanged(usize, String),
+    ApplyArray,
+    CancelArray,
+    /// Mirror the selection about its own center
+    FlipButton(Flip),
+    /// Mirror the selection about an axis chosen on the plan
+    MirrorButton(Flip),
+    /// Mirror the selection about the axis at this coordinate
+    Mirror(Flip, i32),
+    SetRoomButton,
+    /// Change the quantity of the catalog entry at the given index to pack
+    PackQuantityChanged(usize, String),
+    /// Pack the quantities of catalog items in the room
+    PackButton,
+    /// Look for paths moving the selected shape, turning it if allowed
+    MotionButton(bool),
+    /// Look for a path moving the center of the selected shape to this spot
+    PlanMotion(Vec2),
+    AccessButton,
+    /// Change a size of the agent of the accessibility analysis
+    AccessDraftChanged(usize, String),
+    /// Set the entrance of the accessibility analysis
+    SetEntrance(Vec2),
+    EgressButton,
+    /// Change the longest walking distance allowed to an exit
+    EgressLimitChanged(String),
+    AddExit(Vec2),
+    /// Remove the exit at this index in the exits of the plan
+    RemoveExit(usize),
+    CoverageButton,
+    /// Analyze the coverage by the circles of this category
+    CoverageCategory(String),
+    /// Whether the shapes block the coverage of the points behind them
+    CoverageObstaclesToggled(bool),
+    /// Show or hide the occupancy report
+    ReportButton,
+    /// Export the occupancy report as CSV next to the plan file
+    ExportReportButton,
+    OptimizeButton,
+    /// Change a parameter of the layout optimizer
+    OptimizerDraftChanged(usize, String),
+    PreviewOptimization,
+    ApplyOptimization,
+    CancelOptimization,
+    /// Start placing instances of the block
+    BlockButton(BlockId),
+    /// Add an instance of the block with its origin at the given position
+    AddInstance(BlockId, Vec2),
+    EditBlockButton(BlockId),
+    /// Change the field of a shape in the block being edited
+    BlockDraftChanged(usize, usize, String),
+    ApplyBlockEdit,
+    CancelBlockEdit,
     UpdateShape(ShapeId, Shape),
+    /// Update several shapes in a single step
+    UpdateShapes(Vec<(ShapeId, Shape)>),
+    AddDimension(Dimension),
+    RemoveDimension(DimensionId),
     ScaleUp,
     ScaleDown,
     Cancel,
     Undo,
-    Redo
+    Redo,
+    /// Undo or redo commands until the given number of commands is done
+    JumpToHistory(usize),
+    PreviousBranch,
+    NextBranch,
+    /// A canvas program started a gesture, like sizing or dragging a shape
+    GestureStarted,
+    /// A canvas program ended its gesture without sending any other message
+    GestureEnded,
+    CursorMoved(Option<Vec2>),
+    FilePathChanged(String),
+    SaveButton,
+    OpenButton,
 }
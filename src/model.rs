@@ -1,3 +1,18 @@
+pub mod access;
+pub mod array;
+pub mod block;
+pub mod catalog;
+pub mod coverage;
+pub mod dimension;
+pub mod egress;
+pub mod grid;
+pub mod measure;
+pub mod metadata;
+pub mod motion;
+pub mod optimize;
+pub mod packing;
 pub mod plan;
+pub mod report;
+pub mod room;
 pub mod shape;
 pub mod vec2;
@@ -0,0 +1,139 @@
+//! Accessibility of a plan for an agent such as a wheelchair: which shapes
+//! can be reached from an entrance, and where the passages are too narrow
+//! for the agent to turn around
+
+use super::grid::{self, DistanceField, Grid};
+use super::plan::{Plan, ShapeId};
+use super::shape::{Circle, Shape};
+use super::vec2::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Agent {
+    /// Width of the narrowest passage the agent goes through
+    pub width: f32,
+    /// Diameter of the circle the agent needs to turn around
+    pub turning: f32,
+}
+
+#[derive(Debug)]
+pub struct Accessibility {
+    grid: Grid,
+    field: DistanceField,
+    /// Shapes the agent can't get next to
+    pub unreachable: Vec<ShapeId>,
+    /// Reachable points where the agent can't turn around, grouped in
+    /// connected areas
+    pub bottlenecks: Vec<Vec<Vec2>>,
+}
+
+impl Accessibility {
+    /// Analyze the free space of the `plan` for the `agent` coming in at
+    /// `entrance`. The agent is a disk as wide as the narrowest passage it
+    /// goes through, and a shape is reached when the agent gets within a
+    /// step of the grid of it. `None` for an empty plan without a room
+    pub fn new(plan: &Plan, agent: Agent, entrance: Vec2) -> Option<Self> {
+        let grid = Grid::for_plan(plan)?;
+        let clearance = grid::clearance(plan, &grid);
+        let passable: Vec<_> = clearance
+            .iter()
+            .map(|clearance| *clearance >= agent.width / 2.0)
+            .collect();
+        let sources: Vec<_> = grid.nearest(entrance).into_iter().collect();
+        let field = DistanceField::new(&grid, &passable, &sources);
+
+        let reachable: Vec<_> = (0..grid.len())
+            .filter(|index| field.is_reached(*index))
+            .collect();
+        let reach = agent.width / 2.0 + grid.step() as f32;
+        let unreachable = plan
+            .iter()
+            .filter(|(_, shape)| {
+                !reachable.iter().any(|index| {
+                    let point = Shape::from(Circle::new(grid.point(*index), 0));
+                    shape.distance(&point) <= reach
+                })
+            })
+            .map(|(id, _)| *id)
+            .collect();
+
+        let narrow = (0..grid.len())
+            .map(|index| field.is_reached(index) && clearance[index] < agent.turning / 2.0)
+            .collect();
+        let bottlenecks = grid
+            .regions(narrow)
+            .into_iter()
+            .map(|area| area.into_iter().map(|index| grid.point(index)).collect())
+            .collect();
+
+        Some(Self {
+            grid,
+            field,
+            unreachable,
+            bottlenecks,
+        })
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Points of the grid the agent can reach
+    pub fn reachable(&self) -> impl Iterator<Item = Vec2> + '_ {
+        (0..self.grid.len())
+            .filter(|index| self.field.is_reached(*index))
+            .map(|index| self.grid.point(index))
+    }
+
+    /// Shortest path from the entrance to the point of the grid nearest to
+    /// `target`, with its length. `None` if the agent can't get there
+    pub fn path_to(&self, target: Vec2) -> Option<(Vec<Vec2>, f32)> {
+        let index = self.grid.nearest(target)?;
+        let path = self.field.path(index)?;
+        let points = path.into_iter().map(|index| self.grid.point(index)).collect();
+        Some((points, self.field.distance(index)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::room::Room;
+    use crate::model::shape::Rectangle;
+
+    #[test]
+    fn narrow_passages_block_wide_agents() {
+        let mut plan = Plan::default();
+        plan.set_room(Room::new((0, 0).into(), (30, 20).into()));
+        // Wall with a passage 4 units wide, and a desk behind it
+        let walls = [Rectangle::new((14, 0).into(), 2, 8), Rectangle::new((14, 12).into(), 2, 8)];
+        for wall in walls {
+            let id = plan.generate_shape_id();
+            plan.add_shape(id, wall.into());
+        }
+        let desk = plan.generate_shape_id();
+        plan.add_shape(desk, Rectangle::new((22, 8).into(), 4, 2).into());
+
+        let narrow = Agent {
+            width: 3.0,
+            turning: 6.0,
+        };
+        let access = Accessibility::new(&plan, narrow, (4, 10).into()).unwrap();
+        assert!(access.unreachable.is_empty());
+        // The passage is too narrow to turn
+        assert!(access
+            .bottlenecks
+            .iter()
+            .any(|area| area.contains(&Vec2::new(15, 10))));
+        let (path, length) = access.path_to((20, 10).into()).unwrap();
+        assert_eq!(path.first(), Some(&Vec2::new(4, 10)));
+        assert_eq!(length, 16.0);
+
+        let wide = Agent {
+            width: 5.0,
+            turning: 6.0,
+        };
+        let access = Accessibility::new(&plan, wide, (4, 10).into()).unwrap();
+        assert_eq!(access.unreachable, [desk]);
+        assert!(access.path_to((20, 10).into()).is_none());
+    }
+}
@@ -0,0 +1,65 @@
+//! Arrays of copies, laid out in rows and columns
+
+use super::shape::{self, Shape};
+use super::vec2::Vec2;
+
+/// Copies laid out in a grid, the original shapes being the corner cell the
+/// columns and rows start from. A single row or column makes a linear array
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArrayLayout {
+    pub columns: u32,
+    pub rows: u32,
+    /// Space between the bounding boxes of two neighbouring copies
+    pub gap: Vec2,
+    /// Direction of the columns and rows on each axis: 1 to the right and
+    /// down, -1 to the left and up
+    pub direction: Vec2,
+}
+
+impl ArrayLayout {
+    /// Translations from the original `shapes` to each copy, row by row
+    pub fn offsets(&self, shapes: &[Shape]) -> Vec<Vec2> {
+        let Some((top_left, bottom_right)) = shape::bounding_box(shapes) else {
+            return Vec::new();
+        };
+        let size = bottom_right - top_left + self.gap;
+        let step = Vec2::new(size.x * self.direction.x, size.y * self.direction.y);
+
+        (0..self.rows as i32)
+            .flat_map(|row| (0..self.columns as i32).map(move |column| (column, row)))
+            .skip(1)
+            .map(|(column, row)| Vec2::new(column * step.x, row * step.y))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::shape::Rectangle;
+
+    #[test]
+    fn offsets_follow_grid() {
+        let shapes: [Shape; 1] = [Rectangle::new((5, 5).into(), 4, 2).into()];
+        let layout = ArrayLayout {
+            columns: 3,
+            rows: 2,
+            gap: (1, 2).into(),
+            direction: (1, 1).into(),
+        };
+
+        let offsets = layout.offsets(&shapes);
+        let expected: [Vec2; 5] = [(5, 0), (10, 0), (0, 4), (5, 4), (10, 4)].map(Vec2::from);
+        assert_eq!(offsets, expected);
+
+        let linear = ArrayLayout { rows: 1, ..layout };
+        assert_eq!(linear.offsets(&shapes).len(), 2);
+
+        let up_left = ArrayLayout {
+            direction: (-1, -1).into(),
+            ..layout
+        };
+        let expected: [Vec2; 5] = [(-5, 0), (-10, 0), (0, -4), (-5, -4), (-10, -4)].map(Vec2::from);
+        assert_eq!(up_left.offsets(&shapes), expected);
+    }
+}
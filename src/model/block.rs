@@ -0,0 +1,92 @@
+//! Blocks are reusable groups of shapes. A block definition holds the shapes
+//! in local coordinates and each instance of the block is made of regular
+//! shapes of the plan, each one linked to a shape of the definition
+
+use serde::{Deserialize, Serialize};
+
+use super::plan::BlockId;
+use super::shape::{self, Shape};
+use super::vec2::Vec2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Block {
+    name: String,
+    /// Shapes relative to the origin of the block
+    shapes: Vec<Shape>,
+}
+
+/// Link between a shape of the plan and the shape at `index`
+/// in the definition of the block `block_id`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockMember {
+    pub block_id: BlockId,
+    pub index: usize,
+}
+
+impl Block {
+    /// Make a block of the `shapes`, with its origin at the top left
+    /// corner of their bounding box. Return the origin along with the block
+    pub fn from_shapes(name: String, shapes: &[Shape]) -> Option<(Vec2, Self)> {
+        let (origin, _) = shape::bounding_box(shapes)?;
+
+        let shapes = shapes
+            .iter()
+            .map(|shape| {
+                let mut shape = shape.clone();
+                shape.translate(-origin);
+                shape
+            })
+            .collect();
+
+        Some((origin, Self { name, shapes }))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+
+    /// Shapes of an instance of the block with its origin at `pos`
+    pub fn shapes_at(&self, pos: Vec2) -> Vec<Shape> {
+        self.shapes
+            .iter()
+            .map(|shape| {
+                let mut shape = shape.clone();
+                shape.translate(pos);
+                shape
+            })
+            .collect()
+    }
+
+    /// Copy of the block with other `shapes`
+    pub fn with_shapes(&self, shapes: Vec<Shape>) -> Self {
+        Self {
+            name: self.name.clone(),
+            shapes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::shape::{Circle, Rectangle};
+
+    #[test]
+    fn shapes_are_in_local_coordinates() {
+        let shapes: [Shape; 2] = [
+            Rectangle::new((10, 12).into(), 8, 4).into(),
+            Circle::new((14, 20).into(), 2).into(),
+        ];
+
+        let (origin, block) = Block::from_shapes("Desk".into(), &shapes).unwrap();
+        assert_eq!(origin, (10, 12).into());
+        assert_eq!(block.shapes()[1], Circle::new((4, 8).into(), 2).into());
+        assert_eq!(block.shapes_at(origin), shapes);
+
+        assert!(Block::from_shapes("Empty".into(), &[]).is_none());
+    }
+}
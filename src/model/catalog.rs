@@ -0,0 +1,138 @@
+//! Catalog of reusable items, loaded from a JSON definition file listing
+//! the items with their name, their fixed size and their default metadata
+
+use std::path::Path;
+use std::{fmt, fs, io};
+
+use serde::Deserialize;
+
+use super::metadata::Metadata;
+use super::shape::{Circle, Rectangle, Shape};
+use super::vec2::Vec2;
+
+/// Shape of an item, its position is given when it is placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ItemShape {
+    Rectangle { width: u32, height: u32 },
+    Circle { radius: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CatalogEntry {
+    pub name: String,
+    pub shape: ItemShape,
+    #[serde(default)]
+    pub metadata: Metadata,
+}
+
+impl CatalogEntry {
+    /// Shape of the item placed at `pos`
+    pub fn shape_at(&self, pos: Vec2) -> Shape {
+        match self.shape {
+            ItemShape::Rectangle { width, height } => Rectangle::new(pos, width, height).into(),
+            ItemShape::Circle { radius } => Circle::new(pos, radius).into(),
+        }
+    }
+
+    /// Metadata of the placed items, named after the entry
+    /// unless the definition gives another name
+    pub fn metadata(&self) -> Metadata {
+        let mut metadata = self.metadata.clone();
+        metadata.name.get_or_insert_with(|| self.name.clone());
+        metadata
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Catalog {
+    entries: Vec<CatalogEntry>,
+}
+
+#[derive(Debug)]
+pub enum CatalogError {
+    Io(io::Error),
+    Format(serde_json::Error),
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CatalogError::Io(err) => write!(f, "{err}"),
+            CatalogError::Format(err) => write!(f, "invalid catalog: {err}"),
+        }
+    }
+}
+
+impl From<io::Error> for CatalogError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for CatalogError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Format(value)
+    }
+}
+
+impl Catalog {
+    pub fn from_str(content: &str) -> Result<Self, CatalogError> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, CatalogError> {
+        Self::from_str(&fs::read_to_string(path)?)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&CatalogEntry> {
+        self.entries.get(index)
+    }
+
+    pub fn entries(&self) -> &[CatalogEntry] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CATALOG: &str = r#"[
+        {
+            "name": "Desk",
+            "shape": { "Rectangle": { "width": 8, "height": 4 } },
+            "metadata": { "category": "desk" }
+        },
+        {
+            "name": "Round table",
+            "shape": { "Circle": { "radius": 3 } }
+        }
+    ]"#;
+
+    #[test]
+    fn entries_are_loaded() {
+        let catalog = Catalog::from_str(CATALOG).unwrap();
+
+        assert_eq!(catalog.entries().len(), 2);
+        let desk = catalog.get(0).unwrap();
+        assert_eq!(desk.shape_at((2, 3).into()), Rectangle::new((2, 3).into(), 8, 4).into());
+        assert_eq!(
+            desk.metadata(),
+            Metadata {
+                name: Some("Desk".into()),
+                category: Some("desk".into()),
+            }
+        );
+
+        let table = catalog.get(1).unwrap();
+        assert_eq!(table.shape_at((0, 0).into()), Circle::new((0, 0).into(), 3).into());
+        assert_eq!(table.metadata().category, None);
+    }
+
+    #[test]
+    fn invalid_catalog_is_rejected() {
+        let content = r#"[{ "name": "Desk", "shape": { "Triangle": {} } }]"#;
+        assert!(matches!(Catalog::from_str(content), Err(CatalogError::Format(_))));
+    }
+}
@@ -0,0 +1,227 @@
+//! Coverage of the floor of a plan by circles standing for what they emit
+//! around them, like lamps, Wi-Fi access points or sprinklers
+
+use super::grid::Grid;
+use super::plan::{Plan, ShapeId};
+use super::shape::Shape;
+use super::vec2::Vec2;
+
+#[derive(Debug)]
+pub struct Coverage {
+    grid: Grid,
+    /// Number of emitters covering each point of the grid, `None` for the
+    /// points inside of solid shapes which aren't part of the floor
+    counts: Vec<Option<u32>>,
+    /// Circles of the category emitting
+    pub emitters: Vec<ShapeId>,
+    /// Connected areas of the floor no emitter covers
+    pub uncovered: Vec<Vec<Vec2>>,
+}
+
+impl Coverage {
+    /// Coverage of the room of the `plan`, or of its shapes when it has no
+    /// room, by its circles of the `category`. Circles with a category are
+    /// the ranges of emitters, the other shapes are solid and aren't part of
+    /// the floor. With `obstacles`, the solid shapes cast shadows hiding the
+    /// points behind them from the emitters.
+    /// `None` for an empty plan without a room
+    pub fn new(plan: &Plan, category: &str, obstacles: bool) -> Option<Self> {
+        let grid = Grid::for_plan(plan)?;
+
+        let mut emitters = Vec::new();
+        let mut circles = Vec::new();
+        let mut solids = Vec::new();
+        for (id, shape) in plan.iter() {
+            let shape_category = plan
+                .get_metadata(*id)
+                .and_then(|metadata| metadata.category.as_deref());
+            match (shape, shape_category) {
+                (Shape::Circle(circle), Some(shape_category)) => {
+                    if shape_category == category {
+                        emitters.push(*id);
+                        circles.push(circle);
+                    }
+                }
+                _ => solids.push(shape),
+            }
+        }
+
+        let counts: Vec<_> = (0..grid.len())
+            .map(|index| {
+                let point = grid.point(index);
+                if solids.iter().any(|shape| shape.contains(&point)) {
+                    return None;
+                }
+
+                let covering = circles.iter().filter(|circle| {
+                    circle.pos().exact_distance(&point) <= circle.radius() as f32
+                        && !(obstacles
+                            && solids.iter().any(|shape| blocks(shape, circle.pos(), point)))
+                });
+                Some(covering.count() as u32)
+            })
+            .collect();
+
+        let uncovered = grid
+            .regions(counts.iter().map(|count| *count == Some(0)).collect())
+            .into_iter()
+            .map(|region| region.into_iter().map(|index| grid.point(index)).collect())
+            .collect();
+
+        Some(Self {
+            grid,
+            counts,
+            emitters,
+            uncovered,
+        })
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Points of the floor with the number of emitters covering them
+    pub fn points(&self) -> impl Iterator<Item = (Vec2, u32)> + '_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter_map(|(index, count)| Some((self.grid.point(index), (*count)?)))
+    }
+
+    /// Share of the floor covered by at least one emitter, between 0 and 1
+    pub fn covered(&self) -> f32 {
+        self.share(|count| count >= 1)
+    }
+
+    /// Share of the floor covered by several emitters, between 0 and 1
+    pub fn redundant(&self) -> f32 {
+        self.share(|count| count >= 2)
+    }
+
+    /// Average number of emitters covering the covered points
+    pub fn mean_overlap(&self) -> f32 {
+        let (points, total) = self
+            .points()
+            .filter(|(_, count)| *count > 0)
+            .fold((0, 0), |(points, total), (_, count)| (points + 1, total + count));
+        total as f32 / points.max(1) as f32
+    }
+
+    /// Number of emitters covering the point of the grid nearest to `pos`,
+    /// `None` outside of the floor
+    pub fn count_at(&self, pos: Vec2) -> Option<u32> {
+        self.counts[self.grid.nearest(pos)?]
+    }
+
+    fn share(&self, filter: impl Fn(u32) -> bool) -> f32 {
+        let (points, matching) = self
+            .points()
+            .fold((0, 0), |(points, matching), (_, count)| {
+                (points + 1, matching + filter(count) as u32)
+            });
+        matching as f32 / points.max(1) as f32
+    }
+}
+
+/// Categories of the circles of the `plan`, sorted
+pub fn categories(plan: &Plan) -> Vec<String> {
+    let mut categories: Vec<_> = plan
+        .iter()
+        .filter(|(_, shape)| matches!(shape, Shape::Circle(_)))
+        .filter_map(|(id, _)| plan.get_metadata(*id)?.category.clone())
+        .collect();
+    categories.sort();
+    categories.dedup();
+    categories
+}
+
+/// Whether the segment between `from` and `to` goes through the inside of
+/// the `shape`. Segments only touching its border aren't blocked
+fn blocks(shape: &Shape, from: Vec2, to: Vec2) -> bool {
+    let (from_x, from_y) = (from.x as f32, from.y as f32);
+    let (dx, dy) = ((to.x - from.x) as f32, (to.y - from.y) as f32);
+
+    match shape {
+        Shape::Circle(circle) => {
+            // Distance from the center to the nearest point of the segment
+            let (cx, cy) = (circle.pos().x as f32 - from_x, circle.pos().y as f32 - from_y);
+            let length = dx * dx + dy * dy;
+            let t = if length > 0.0 {
+                ((cx * dx + cy * dy) / length).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            f32::hypot(cx - t * dx, cy - t * dy) < circle.radius() as f32
+        }
+        Shape::Rectangle(rect) => {
+            // Part of the segment between the sides of the rectangle along
+            // each axis
+            let (mut enter, mut exit) = (0.0f32, 1.0f32);
+            let axes = [
+                (from_x, dx, rect.pos().x, rect.pos().x + rect.width() as i32),
+                (from_y, dy, rect.pos().y, rect.pos().y + rect.height() as i32),
+            ];
+            for (start, delta, min, max) in axes {
+                let (min, max) = (min as f32, max as f32);
+                if delta == 0.0 {
+                    if start <= min || start >= max {
+                        return false;
+                    }
+                } else {
+                    let (t1, t2) = ((min - start) / delta, (max - start) / delta);
+                    enter = enter.max(t1.min(t2));
+                    exit = exit.min(t1.max(t2));
+                }
+            }
+            enter < exit
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::metadata::Metadata;
+    use crate::model::room::Room;
+    use crate::model::shape::{Circle, Rectangle};
+
+    fn lamp(plan: &mut Plan, pos: Vec2, radius: u32) {
+        let id = plan.generate_shape_id();
+        plan.add_shape(id, Circle::new(pos, radius).into());
+        let metadata = Metadata {
+            name: None,
+            category: Some("lamp".into()),
+        };
+        plan.set_metadata(id, metadata);
+    }
+
+    #[test]
+    fn obstacles_cast_shadows() {
+        let mut plan = Plan::default();
+        plan.set_room(Room::new((0, 0).into(), (20, 10).into()));
+        lamp(&mut plan, (5, 5).into(), 6);
+        lamp(&mut plan, (9, 5).into(), 6);
+        let shelf = plan.generate_shape_id();
+        plan.add_shape(shelf, Rectangle::new((12, 0).into(), 1, 10).into());
+        assert_eq!(categories(&plan), ["lamp"]);
+
+        let coverage = Coverage::new(&plan, "lamp", false).unwrap();
+        assert_eq!(coverage.emitters.len(), 2);
+        assert_eq!(coverage.count_at((7, 5).into()), Some(2));
+        assert_eq!(coverage.count_at((14, 5).into()), Some(1));
+        assert_eq!(coverage.count_at((12, 5).into()), None);
+        assert!(coverage.redundant() > 0.0);
+        assert!(coverage.mean_overlap() > 1.0);
+        // Past the reach of the lamps
+        assert!(coverage
+            .uncovered
+            .iter()
+            .any(|region| region.contains(&Vec2::new(19, 5))));
+
+        let shadowed = Coverage::new(&plan, "lamp", true).unwrap();
+        assert_eq!(shadowed.count_at((14, 5).into()), Some(0));
+        assert!(shadowed.covered() < coverage.covered());
+
+        assert!(Coverage::new(&plan, "sprinkler", false).unwrap().covered() == 0.0);
+    }
+}
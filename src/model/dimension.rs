@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+use super::plan::ShapeId;
+use super::shape::Shape;
+use super::vec2::Vec2;
+
+/// Distance in world units between a shape and the line of its dimension
+pub const DEFAULT_DIMENSION_OFFSET: i32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DimensionKind {
+    Width,
+    Height,
+    Radius,
+}
+
+impl DimensionKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DimensionKind::Width => "width",
+            DimensionKind::Height => "height",
+            DimensionKind::Radius => "radius",
+        }
+    }
+}
+
+/// A dimension line attached to a shape. It only references the shape,
+/// so its value and position always follow the current state of the shape
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dimension {
+    shape_id: ShapeId,
+    kind: DimensionKind,
+    offset: i32,
+}
+
+/// Resolved dimension line, from `start` to `end` measuring `value`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DimensionLine {
+    pub start: Vec2,
+    pub end: Vec2,
+    pub value: u32,
+}
+
+impl Dimension {
+    pub fn new(shape_id: ShapeId, kind: DimensionKind) -> Self {
+        Self {
+            shape_id,
+            kind,
+            offset: DEFAULT_DIMENSION_OFFSET,
+        }
+    }
+
+    pub fn shape_id(&self) -> ShapeId {
+        self.shape_id
+    }
+
+    pub fn kind(&self) -> DimensionKind {
+        self.kind
+    }
+
+    /// Compute the line of the dimension for the `shape` it is attached to.
+    /// Return `None` if the kind of dimension doesn't apply to the shape
+    pub fn line(&self, shape: &Shape) -> Option<DimensionLine> {
+        match (self.kind, shape) {
+            (DimensionKind::Width, Shape::Rectangle(rect)) => {
+                let start = rect.pos() - Vec2::new(0, self.offset);
+                Some(DimensionLine {
+                    start,
+                    end: start + Vec2::new(rect.width() as i32, 0),
+                    value: rect.width(),
+                })
+            }
+            (DimensionKind::Height, Shape::Rectangle(rect)) => {
+                let start = rect.pos() - Vec2::new(self.offset, 0);
+                Some(DimensionLine {
+                    start,
+                    end: start + Vec2::new(0, rect.height() as i32),
+                    value: rect.height(),
+                })
+            }
+            (DimensionKind::Radius, Shape::Circle(circle)) => Some(DimensionLine {
+                start: circle.pos(),
+                end: circle.pos() + Vec2::new(circle.radius() as i32, 0),
+                value: circle.radius(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::plan::Plan;
+    use crate::model::shape::{Circle, Rectangle};
+
+    #[test]
+    fn line_follows_shape() {
+        let shape_id = Plan::default().generate_shape_id();
+        let dimension = Dimension::new(shape_id, DimensionKind::Width);
+        let mut shape: Shape = Rectangle::new((2, 3).into(), 4, 5).into();
+
+        let line = dimension.line(&shape).unwrap();
+        assert_eq!(line.start, (2, 2).into());
+        assert_eq!(line.end, (6, 2).into());
+        assert_eq!(line.value, 4);
+
+        shape.move_to((10, 10).into());
+        let line = dimension.line(&shape).unwrap();
+        assert_eq!(line.start, (10, 9).into());
+        assert_eq!(line.end, (14, 9).into());
+    }
+
+    #[test]
+    fn line_of_height_and_radius() {
+        let shape_id = Plan::default().generate_shape_id();
+        let rect: Shape = Rectangle::new((2, 3).into(), 4, 5).into();
+        let circle: Shape = Circle::new((2, 3).into(), 7).into();
+
+        let height = Dimension::new(shape_id, DimensionKind::Height);
+        let radius = Dimension::new(shape_id, DimensionKind::Radius);
+
+        assert_eq!(height.line(&rect).unwrap().value, 5);
+        assert_eq!(radius.line(&circle).unwrap().end, (9, 3).into());
+        assert!(height.line(&circle).is_none());
+        assert!(radius.line(&rect).is_none());
+    }
+}
@@ -0,0 +1,120 @@
+//! Walking distance from the free space of a plan to its nearest exit,
+//! for safety reviews
+
+use super::grid::{self, DistanceField, Grid};
+use super::plan::Plan;
+use super::vec2::Vec2;
+
+#[derive(Debug)]
+pub struct Egress {
+    grid: Grid,
+    field: DistanceField,
+    /// Whether each point of the grid is outside of the shapes
+    free: Vec<bool>,
+    max_distance: f32,
+}
+
+impl Egress {
+    /// Walking distances to the exits of the `plan` around its shapes.
+    /// Points farther than `max_distance` from every exit are too far.
+    /// `None` for a plan without exits, or empty without a room
+    pub fn new(plan: &Plan, max_distance: f32) -> Option<Self> {
+        let grid = Grid::for_plan(plan)?;
+        let free: Vec<_> = grid::clearance(plan, &grid)
+            .into_iter()
+            .map(|clearance| clearance > 0.0)
+            .collect();
+
+        // Exits are usually on the walls, so they are entered from the
+        // nearest free point
+        let sources: Vec<_> = plan
+            .exits()
+            .iter()
+            .filter_map(|exit| {
+                (0..grid.len())
+                    .filter(|index| free[*index])
+                    .min_by_key(|index| {
+                        let offset = grid.point(*index) - *exit;
+                        offset.x as i64 * offset.x as i64 + offset.y as i64 * offset.y as i64
+                    })
+            })
+            .collect();
+        if sources.is_empty() {
+            return None;
+        }
+
+        let field = DistanceField::new(&grid, &free, &sources);
+        Some(Self {
+            grid,
+            field,
+            free,
+            max_distance,
+        })
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn max_distance(&self) -> f32 {
+        self.max_distance
+    }
+
+    /// Free points of the grid with their walking distance to the nearest
+    /// exit, `None` when they have no way out
+    pub fn points(&self) -> impl Iterator<Item = (Vec2, Option<f32>)> + '_ {
+        (0..self.grid.len())
+            .filter(|index| self.free[*index])
+            .map(|index| (self.grid.point(index), self.field.distance(index)))
+    }
+
+    /// Free points farther than the maximum distance from every exit,
+    /// or without a way out
+    pub fn too_far(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.points()
+            .filter(|(_, distance)| distance.is_none_or(|distance| distance > self.max_distance))
+            .map(|(point, _)| point)
+    }
+
+    /// Longest walking distance to an exit among the points with a way out
+    pub fn farthest(&self) -> Option<f32> {
+        self.points().filter_map(|(_, distance)| distance).reduce(f32::max)
+    }
+
+    /// Walking distance to the nearest exit from the point of the grid
+    /// nearest to `pos`, `None` in a shape or without a way out
+    pub fn distance_at(&self, pos: Vec2) -> Option<f32> {
+        self.field.distance(self.grid.nearest(pos)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::room::Room;
+    use crate::model::shape::Rectangle;
+
+    #[test]
+    fn distances_route_around_shapes() {
+        let mut plan = Plan::default();
+        plan.set_room(Room::new((0, 0).into(), (20, 10).into()));
+        let wall = plan.generate_shape_id();
+        plan.add_shape(wall, Rectangle::new((8, 0).into(), 2, 8).into());
+        assert!(Egress::new(&plan, 12.0).is_none());
+
+        plan.set_exits(vec![(0, 5).into()]);
+        let egress = Egress::new(&plan, 12.0).unwrap();
+        assert_eq!(egress.distance_at((1, 5).into()), Some(0.0));
+        // Around the wall rather than through it
+        assert!(egress.distance_at((12, 2).into()).unwrap() > 14.0);
+        assert_eq!(egress.distance_at((9, 4).into()), None);
+        assert!(egress.too_far().any(|point| point == Vec2::new(18, 5)));
+        assert!(!egress.too_far().any(|point| point == Vec2::new(5, 5)));
+
+        // A second exit behind the wall brings every point close enough
+        plan.set_exits(vec![(0, 5).into(), (20, 5).into()]);
+        let egress = Egress::new(&plan, 12.0).unwrap();
+        assert_eq!(egress.too_far().count(), 0);
+        assert!(egress.farthest().unwrap() <= 12.0);
+    }
+}
@@ -0,0 +1,278 @@
+//! Regular grid of points over a plan, on which the analyses of the free
+//! space discretize it, and the steps between neighbouring positions shared
+//! with the motion planning
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use super::plan::Plan;
+use super::shape::{self, Circle, Shape};
+use super::vec2::Vec2;
+
+/// Free space around the shapes covered when the plan has no room
+const MARGIN: i32 = 20;
+/// Largest number of points of a grid, larger plans get a coarser grid
+const MAX_POINTS: usize = 250_000;
+
+/// Costs of the steps between neighbouring positions, a diagonal being
+/// about √2 straight steps
+pub const STRAIGHT_COST: u32 = 10;
+pub const DIAGONAL_COST: u32 = 14;
+
+/// Top left and bottom right corners of the area covered on the `plan`: its
+/// room, or its shapes and the `extra` ones with a margin when it has no room.
+/// `None` without a room nor any shape
+pub fn area<'a>(
+    plan: &'a Plan,
+    extra: impl IntoIterator<Item = &'a Shape>,
+) -> Option<(Vec2, Vec2)> {
+    if let Some(room) = plan.room() {
+        return Some((room.top_left(), room.bottom_right()));
+    }
+
+    let shapes = plan.iter().map(|(_, shape)| shape).chain(extra);
+    let (min, max) = shape::bounding_box(shapes)?;
+    let margin = Vec2::new(MARGIN, MARGIN);
+    Some((min - margin, max + margin))
+}
+
+/// Cost of a step by `dx` and `dy` to a neighbouring position, `None` when
+/// `is_free` tells the step is blocked: the position reached must be free,
+/// and a diagonal step can't cut the corners of the positions beside it
+pub fn step_cost(dx: i32, dy: i32, is_free: impl Fn(i32, i32) -> bool) -> Option<u32> {
+    let diagonal = dx != 0 && dy != 0;
+    let free = is_free(dx, dy) && (!diagonal || is_free(dx, 0) && is_free(0, dy));
+    free.then_some(if diagonal { DIAGONAL_COST } else { STRAIGHT_COST })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid {
+    origin: Vec2,
+    /// Distance between two neighbouring points
+    step: i32,
+    columns: usize,
+    rows: usize,
+}
+
+impl Grid {
+    /// Grid covering the room of the `plan`, or its shapes with a margin
+    /// when it has no room. `None` for an empty plan without a room
+    pub fn for_plan(plan: &Plan) -> Option<Self> {
+        let (min, max) = area(plan, [])?;
+
+        let size = max - min;
+        let area = (size.x as f64 + 1.0) * (size.y as f64 + 1.0);
+        let step = (area / MAX_POINTS as f64).sqrt().ceil().max(1.0) as i32;
+
+        Some(Self {
+            origin: min,
+            step,
+            columns: (size.x / step) as usize + 1,
+            rows: (size.y / step) as usize + 1,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    pub fn step(&self) -> i32 {
+        self.step
+    }
+
+    pub fn point(&self, index: usize) -> Vec2 {
+        let column = (index % self.columns) as i32;
+        let row = (index / self.columns) as i32;
+        self.origin + Vec2::new(column * self.step, row * self.step)
+    }
+
+    /// Index of the point nearest to `pos`, `None` outside of the grid
+    pub fn nearest(&self, pos: Vec2) -> Option<usize> {
+        let relative = pos - self.origin;
+        let column = (relative.x as f32 / self.step as f32).round() as i64;
+        let row = (relative.y as f32 / self.step as f32).round() as i64;
+        self.index(column, row)
+    }
+
+    /// Indices of the up to 8 points around the point at `index`
+    pub fn neighbours(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        (-1..=1)
+            .flat_map(|dx| (-1..=1).map(move |dy| (dx, dy)))
+            .filter(|offset| *offset != (0, 0))
+            .filter_map(move |(dx, dy)| self.offset(index, dx, dy))
+    }
+
+    /// Connected areas of the points marked in `mask`, as their indices
+    pub fn regions(&self, mut mask: Vec<bool>) -> Vec<Vec<usize>> {
+        let mut regions = Vec::new();
+        for start in 0..self.len() {
+            if !mask[start] {
+                continue;
+            }
+            mask[start] = false;
+            let mut region = vec![start];
+            let mut next = 0;
+            while let Some(index) = region.get(next).copied() {
+                for neighbour in self.neighbours(index) {
+                    if mask[neighbour] {
+                        mask[neighbour] = false;
+                        region.push(neighbour);
+                    }
+                }
+                next += 1;
+            }
+            regions.push(region);
+        }
+        regions
+    }
+
+    /// Index of the point at `offset` steps from the point at `index`
+    fn offset(&self, index: usize, dx: i64, dy: i64) -> Option<usize> {
+        let column = (index % self.columns) as i64 + dx;
+        let row = (index / self.columns) as i64 + dy;
+        self.index(column, row)
+    }
+
+    fn index(&self, column: i64, row: i64) -> Option<usize> {
+        let inside = (0..self.columns as i64).contains(&column)
+            && (0..self.rows as i64).contains(&row);
+        inside.then(|| row as usize * self.columns + column as usize)
+    }
+}
+
+/// Distance from each point of the `grid` to the nearest shape of the `plan`
+/// or wall of its room, 0 inside of the shapes
+pub fn clearance(plan: &Plan, grid: &Grid) -> Vec<f32> {
+    (0..grid.len())
+        .map(|index| {
+            let point = grid.point(index);
+            let point_shape = Shape::from(Circle::new(point, 0));
+
+            let walls = plan.room().map_or(f32::INFINITY, |room| {
+                let gaps = [
+                    point.x - room.top_left().x,
+                    point.y - room.top_left().y,
+                    room.bottom_right().x - point.x,
+                    room.bottom_right().y - point.y,
+                ];
+                gaps.into_iter().min().unwrap_or(0).max(0) as f32
+            });
+
+            plan.iter()
+                .map(|(_, shape)| shape.distance(&point_shape))
+                .fold(walls, f32::min)
+        })
+        .collect()
+}
+
+/// Walking distances over the points of a grid from a set of sources,
+/// moving between neighbouring points, diagonals included
+#[derive(Debug)]
+pub struct DistanceField {
+    step: i32,
+    /// Cost from the nearest source of each reached point,
+    /// in tenths of the step of the grid
+    cost: Vec<Option<u32>>,
+    /// Previous point on the shortest path to each reached point
+    previous: Vec<Option<usize>>,
+}
+
+impl DistanceField {
+    /// Distances from the `sources` through the `passable` points of the `grid`.
+    /// Diagonal steps can't cut the corners of impassable points
+    pub fn new(grid: &Grid, passable: &[bool], sources: &[usize]) -> Self {
+        let mut cost = vec![None; grid.len()];
+        let mut previous = vec![None; grid.len()];
+        let mut queue = BinaryHeap::new();
+
+        for source in sources.iter().copied().filter(|source| passable[*source]) {
+            cost[source] = Some(0);
+            queue.push(Reverse((0, source)));
+        }
+
+        while let Some(Reverse((current_cost, index))) = queue.pop() {
+            if cost[index].is_some_and(|known| known < current_cost) {
+                continue;
+            }
+
+            let free = |dx: i32, dy: i32| {
+                grid.offset(index, dx.into(), dy.into()).is_some_and(|i| passable[i])
+            };
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    let Some(next) = grid.offset(index, dx.into(), dy.into()) else {
+                        continue;
+                    };
+                    let Some(step_cost) = step_cost(dx, dy, free).filter(|_| next != index) else {
+                        continue;
+                    };
+
+                    let next_cost = current_cost + step_cost;
+                    if cost[next].is_none_or(|known| next_cost < known) {
+                        cost[next] = Some(next_cost);
+                        previous[next] = Some(index);
+                        queue.push(Reverse((next_cost, next)));
+                    }
+                }
+            }
+        }
+
+        Self {
+            step: grid.step(),
+            cost,
+            previous,
+        }
+    }
+
+    pub fn is_reached(&self, index: usize) -> bool {
+        self.cost[index].is_some()
+    }
+
+    /// Walking distance from the nearest source to the point at `index`,
+    /// in world units
+    pub fn distance(&self, index: usize) -> Option<f32> {
+        self.cost[index].map(|cost| cost as f32 / STRAIGHT_COST as f32 * self.step as f32)
+    }
+
+    /// Points of the shortest path from the nearest source to `index`
+    pub fn path(&self, index: usize) -> Option<Vec<usize>> {
+        self.cost[index]?;
+
+        let mut path = vec![index];
+        while let Some(previous) = self.previous[*path.last().unwrap()] {
+            path.push(previous);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::room::Room;
+    use crate::model::shape::Rectangle;
+
+    #[test]
+    fn distances_go_around_shapes() {
+        let mut plan = Plan::default();
+        plan.set_room(Room::new((0, 0).into(), (10, 10).into()));
+        let wall = plan.generate_shape_id();
+        plan.add_shape(wall, Rectangle::new((4, 0).into(), 2, 8).into());
+
+        let grid = Grid::for_plan(&plan).unwrap();
+        assert_eq!(grid.len(), 121);
+        let clearance = clearance(&plan, &grid);
+        let passable: Vec<_> = clearance.iter().map(|clearance| *clearance > 0.0).collect();
+        let source = grid.nearest((2, 2).into()).unwrap();
+        let field = DistanceField::new(&grid, &passable, &[source]);
+
+        // Around the bottom of the wall rather than through it
+        let target = grid.nearest((8, 2).into()).unwrap();
+        let path = field.path(target).unwrap();
+        assert!(path.iter().any(|index| grid.point(*index).y == 9));
+        assert!(field.distance(target).unwrap() > 14.0);
+        // Points on the walls of the room aren't reachable
+        assert!(!field.is_reached(grid.nearest((0, 5).into()).unwrap()));
+    }
+}
@@ -0,0 +1,57 @@
+//! Measurements on sequences of points in world units
+
+use super::vec2::Vec2;
+
+/// Total length of the polyline going through all the `points`
+pub fn polyline_length(points: &[Vec2]) -> f32 {
+    points
+        .windows(2)
+        .map(|segment| segment[0].exact_distance(&segment[1]))
+        .sum()
+}
+
+/// Area of the polygon defined by `points`, the last point being
+/// linked back to the first one. Computed with the shoelace formula
+pub fn polygon_area(points: &[Vec2]) -> f32 {
+    let Some(last) = points.last() else {
+        return 0.0;
+    };
+
+    let double_area: i64 = std::iter::once(last)
+        .chain(points)
+        .zip(points)
+        .map(|(a, b)| a.x as i64 * b.y as i64 - b.x as i64 * a.y as i64)
+        .sum();
+
+    double_area.abs() as f32 / 2.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn polyline_length_sums_segments() {
+        let points = [(0, 0).into(), (3, 4).into(), (3, 10).into()];
+
+        assert_eq!(polyline_length(&points), 11.0);
+        assert_eq!(polyline_length(&points[..1]), 0.0);
+    }
+
+    #[test]
+    fn polygon_area_any_orientation() {
+        let mut points: Vec<Vec2> = vec![(0, 0).into(), (4, 0).into(), (4, 3).into(), (0, 3).into()];
+
+        assert_eq!(polygon_area(&points), 12.0);
+        points.reverse();
+        assert_eq!(polygon_area(&points), 12.0);
+    }
+
+    #[test]
+    fn polygon_area_degenerate() {
+        let points: Vec<Vec2> = vec![(0, 0).into(), (4, 4).into()];
+
+        assert_eq!(polygon_area(&points), 0.0);
+        assert_eq!(polygon_area(&[]), 0.0);
+    }
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// Information describing what a shape stands for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metadata {
+    /// Name of the item, like "Desk"
+    pub name: Option<String>,
+    /// Kind of item, like "table" or "storage"
+    pub category: Option<String>,
+}
@@ -0,0 +1,297 @@
+//! Motion planning of a shape through a plan, to check whether an item can
+//! physically travel from one spot to another.
+//!
+//! The configuration space is the integer positions of the shape, and its
+//! two orientations when it may turn by a quarter. It is searched with A*,
+//! each step moving the shape by one unit, diagonals included, or turning it
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+
+use super::grid::{self, DIAGONAL_COST, STRAIGHT_COST};
+use super::plan::{Plan, ShapeId};
+use super::shape::{Circle, Rectangle, Shape};
+use super::vec2::Vec2;
+
+/// Largest number of configurations explored before giving up
+const MAX_CONFIGURATIONS: usize = 200_000;
+
+/// Cost of turning the shape, like a straight step
+const TURN_COST: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathError {
+    /// Every configuration reachable from the start was explored
+    Unreachable,
+    /// The search gave up before exploring every reachable configuration,
+    /// there may still be a path
+    LimitReached,
+}
+
+/// Position and orientation of the moving shape, relative to its start
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Configuration {
+    offset: Vec2,
+    turned: bool,
+}
+
+/// Rounded center of the bounding box of the `shape`
+pub fn center(shape: &Shape) -> Vec2 {
+    let (top_left, bottom_right) = shape.bounds();
+    Vec2::new((top_left.x + bottom_right.x) / 2, (top_left.y + bottom_right.y) / 2)
+}
+
+/// The `shape` moved so that its center is at `pos`
+pub fn centered_at(shape: &Shape, pos: Vec2) -> Shape {
+    let mut shape = shape.clone();
+    shape.translate(pos - center(&shape));
+    shape
+}
+
+/// The `shape` turned by a quarter about its center
+fn turned(shape: &Shape) -> Shape {
+    match shape {
+        Shape::Circle(_) => shape.clone(),
+        Shape::Rectangle(rect) => {
+            let turned = Rectangle::new(rect.pos(), rect.height(), rect.width()).into();
+            centered_at(&turned, center(shape))
+        }
+    }
+}
+
+struct Search<'a> {
+    obstacles: Vec<&'a Shape>,
+    /// Top left and bottom right corners of the area the shape must stay in
+    area: (Vec2, Vec2),
+    shape: Shape,
+    turned: Shape,
+}
+
+impl Search<'_> {
+    fn shape_at(&self, configuration: Configuration) -> Shape {
+        let mut shape = match configuration.turned {
+            false => self.shape.clone(),
+            true => self.turned.clone(),
+        };
+        shape.translate(configuration.offset);
+        shape
+    }
+
+    fn is_free(&self, shape: &Shape) -> bool {
+        let (top_left, bottom_right) = shape.bounds();
+        let (min, max) = self.area;
+        top_left.x >= min.x
+            && top_left.y >= min.y
+            && bottom_right.x <= max.x
+            && bottom_right.y <= max.y
+            && self.obstacles.iter().all(|obstacle| obstacle.is_disjoint(shape))
+    }
+
+    /// Whether the shape can turn at `offset`. The turn is checked against
+    /// the disk swept by the shape, which is conservative
+    fn can_turn(&self, offset: Vec2) -> bool {
+        let (top_left, bottom_right) = self.shape.bounds();
+        let size = bottom_right - top_left;
+        let radius = (f64::from(size.x).hypot(f64::from(size.y)) / 2.0).ceil() as u32;
+        let sweep = Circle::new(center(&self.shape) + offset, radius).into();
+        self.is_free(&sweep)
+    }
+
+    /// Configurations reachable in one step, with the cost of the step
+    fn neighbours(&self, from: Configuration, rotation: bool) -> Vec<(Configuration, u32)> {
+        let is_free = |dx, dy| {
+            self.is_free(&self.shape_at(Configuration {
+                offset: from.offset + Vec2::new(dx, dy),
+                turned: from.turned,
+            }))
+        };
+
+        let mut neighbours = Vec::new();
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if (dx, dy) == (0, 0) {
+                    continue;
+                }
+                if let Some(cost) = grid::step_cost(dx, dy, is_free) {
+                    let offset = from.offset + Vec2::new(dx, dy);
+                    neighbours.push((Configuration { offset, ..from }, cost));
+                }
+            }
+        }
+
+        let other = Configuration {
+            turned: !from.turned,
+            ..from
+        };
+        if rotation && self.is_free(&self.shape_at(other)) && self.can_turn(from.offset) {
+            neighbours.push((other, TURN_COST));
+        }
+
+        neighbours
+    }
+}
+
+/// Lower bound of the cost from `offset` to `goal`
+fn heuristic(offset: Vec2, goal: Vec2) -> u32 {
+    let dx = offset.x.abs_diff(goal.x);
+    let dy = offset.y.abs_diff(goal.y);
+    DIAGONAL_COST * dx.min(dy) + STRAIGHT_COST * (dx.max(dy) - dx.min(dy))
+}
+
+/// Collision-free path moving the shape `shape_id` of the `plan` until its
+/// center is at `target`, turning it on the way if `rotation` is allowed.
+/// The shape stays in the room, or near the other shapes without a room.
+///
+/// Return the states of the shape where the path changes direction, from
+/// its current state to the target
+pub fn find_path(
+    plan: &Plan,
+    shape_id: ShapeId,
+    target: Vec2,
+    rotation: bool,
+) -> Result<Vec<Shape>, PathError> {
+    search_path(plan, shape_id, target, rotation, MAX_CONFIGURATIONS)
+}
+
+/// `find_path` exploring at most `max_configurations`
+fn search_path(
+    plan: &Plan,
+    shape_id: ShapeId,
+    target: Vec2,
+    rotation: bool,
+    max_configurations: usize,
+) -> Result<Vec<Shape>, PathError> {
+    let shape = plan.get_shape(shape_id).ok_or(PathError::Unreachable)?.clone();
+    let goal = target - center(&shape);
+
+    let goal_shape = centered_at(&shape, target);
+    let area = grid::area(plan, [&goal_shape]).ok_or(PathError::Unreachable)?;
+    let search = Search {
+        obstacles: plan
+            .iter()
+            .filter(|(id, _)| *id != shape_id)
+            .map(|(_, shape)| shape)
+            .collect(),
+        area,
+        turned: turned(&shape),
+        shape,
+    };
+
+    let start = Configuration {
+        offset: Vec2::default(),
+        turned: false,
+    };
+    // Cost from the start and previous configuration of the explored ones
+    let mut explored: BTreeMap<Configuration, (u32, Option<Configuration>)> = BTreeMap::new();
+    let mut queue = BinaryHeap::new();
+    explored.insert(start, (0, None));
+    queue.push(Reverse((heuristic(start.offset, goal), 0, start)));
+
+    while let Some(Reverse((_, cost, configuration))) = queue.pop() {
+        if configuration.offset == goal {
+            return Ok(waypoints(&search, &explored, configuration));
+        }
+        if explored.len() > max_configurations {
+            return Err(PathError::LimitReached);
+        }
+        if cost > explored[&configuration].0 {
+            continue;
+        }
+
+        for (next, step_cost) in search.neighbours(configuration, rotation) {
+            let next_cost = cost + step_cost;
+            if explored.get(&next).is_some_and(|(known, _)| *known <= next_cost) {
+                continue;
+            }
+            explored.insert(next, (next_cost, Some(configuration)));
+            queue.push(Reverse((next_cost + heuristic(next.offset, goal), next_cost, next)));
+        }
+    }
+
+    Err(PathError::Unreachable)
+}
+
+/// States of the shape along the path ending at `end`, keeping the start,
+/// the end and the configurations where the path changes direction or turns
+fn waypoints(
+    search: &Search,
+    explored: &BTreeMap<Configuration, (u32, Option<Configuration>)>,
+    end: Configuration,
+) -> Vec<Shape> {
+    let mut path = vec![end];
+    while let Some((_, Some(previous))) = explored.get(path.last().unwrap()) {
+        path.push(*previous);
+    }
+    path.reverse();
+
+    let mut kept = vec![path[0]];
+    for window in path.windows(3) {
+        let [previous, current, next] = window else {
+            continue;
+        };
+        let same_direction = current.offset - previous.offset == next.offset - current.offset;
+        if !same_direction || previous.turned != current.turned {
+            kept.push(*current);
+        }
+    }
+    if path.len() > 1 {
+        kept.push(end);
+    }
+
+    kept.into_iter().map(|configuration| search.shape_at(configuration)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::room::Room;
+
+    /// Plan split by a wall with a gap 3 units high, and a table 2 units
+    /// wide and 6 units high on the left of the wall
+    fn plan_with_wall() -> (Plan, ShapeId) {
+        let mut plan = Plan::default();
+        plan.set_room(Room::new((0, 0).into(), (30, 20).into()));
+        let walls = [Rectangle::new((14, 0).into(), 2, 8), Rectangle::new((14, 11).into(), 2, 9)];
+        for wall in walls {
+            let id = plan.generate_shape_id();
+            plan.add_shape(id, wall.into());
+        }
+        let table = plan.generate_shape_id();
+        plan.add_shape(table, Rectangle::new((4, 7).into(), 2, 6).into());
+        (plan, table)
+    }
+
+    #[test]
+    fn path_avoids_shapes() {
+        let (plan, table) = plan_with_wall();
+
+        let path = find_path(&plan, table, (10, 3).into(), false).unwrap();
+        assert_eq!(path.first(), plan.get_shape(table));
+        assert_eq!(center(path.last().unwrap()), (10, 3).into());
+        assert!(path.iter().all(|shape| plan.is_disjoint(shape, Some(table))));
+
+        // The table is too high to go through the gap of the wall
+        let result = find_path(&plan, table, (24, 10).into(), false);
+        assert_eq!(result, Err(PathError::Unreachable));
+    }
+
+    #[test]
+    fn search_gives_up_past_its_limit() {
+        let (plan, table) = plan_with_wall();
+
+        let result = search_path(&plan, table, (24, 10).into(), true, 50);
+        assert_eq!(result, Err(PathError::LimitReached));
+    }
+
+    #[test]
+    fn turning_lets_shape_through_gap() {
+        let (plan, table) = plan_with_wall();
+
+        let path = find_path(&plan, table, (24, 10).into(), true).unwrap();
+        assert_eq!(center(path.last().unwrap()), (24, 10).into());
+        assert!(path
+            .iter()
+            .any(|shape| matches!(shape, Shape::Rectangle(rect) if rect.width() == 6)));
+        assert!(path.iter().all(|shape| plan.is_disjoint(shape, Some(table))));
+    }
+}
@@ -0,0 +1,384 @@
+//! Layout optimization by simulated annealing.
+//!
+//! The optimizer moves shapes one group at a time by small random offsets,
+//! keeping them disjoint from every other shape and inside the room, and
+//! scores the layouts with weighted cost functions. Worse layouts are
+//! accepted with a probability decreasing as the temperature cools down,
+//! which lets the search escape local minima
+
+use std::ops::Range;
+
+use super::plan::{Plan, ShapeId};
+use super::shape::Shape;
+use crate::utils::rng::Rng;
+
+/// Temperature at the start of the search, in units of cost
+const INITIAL_TEMPERATURE: f64 = 5.0;
+/// Largest offset of a move at the start of the search, in world units
+const MAX_STEP: f64 = 5.0;
+
+/// Shapes of a plan being rearranged by the optimizer
+pub struct Layout<'a> {
+    plan: &'a Plan,
+    /// Current state of the shapes being moved
+    moving: Vec<(ShapeId, Shape)>,
+    /// Shapes of the plan which stay in place
+    others: Vec<(ShapeId, Shape)>,
+    /// Ranges of `moving` moved together, one per group or lone shape
+    units: Vec<Range<usize>>,
+}
+
+impl<'a> Layout<'a> {
+    /// Layout moving the shapes `shape_ids` along with the other members of
+    /// their group. The shapes of block instances stay in place, as well as
+    /// the groups they belong to, so the instances keep the block layout
+    pub fn new(plan: &'a Plan, shape_ids: &[ShapeId]) -> Self {
+        let mut moving = Vec::new();
+        let mut units = Vec::new();
+        for shape_id in shape_ids {
+            if moving.iter().any(|(id, _)| id == shape_id) {
+                continue;
+            }
+            let group = plan.group_of(*shape_id);
+            if group.iter().any(|id| plan.get_block_member(*id).is_some()) {
+                continue;
+            }
+
+            let start = moving.len();
+            moving.extend(
+                group
+                    .into_iter()
+                    .filter_map(|id| Some((id, plan.get_shape(id)?.clone()))),
+            );
+            if moving.len() > start {
+                units.push(start..moving.len());
+            }
+        }
+
+        let others = plan
+            .iter()
+            .filter(|(id, _)| !moving.iter().any(|(moving_id, _)| moving_id == id))
+            .cloned()
+            .collect();
+        Self {
+            plan,
+            moving,
+            others,
+            units,
+        }
+    }
+
+    pub fn plan(&self) -> &Plan {
+        self.plan
+    }
+
+    pub fn moving(&self) -> &[(ShapeId, Shape)] {
+        &self.moving
+    }
+
+    /// Shapes of the plan which stay in place
+    pub fn others(&self) -> &[(ShapeId, Shape)] {
+        &self.others
+    }
+
+    /// Whether the moving shapes of the `unit` overlap no other shape
+    /// and stay in the room
+    fn is_valid(&self, unit: &Range<usize>) -> bool {
+        self.moving[unit.clone()].iter().all(|(_, shape)| {
+            let in_room = self.plan.room().is_none_or(|room| room.contains(shape));
+
+            in_room
+                && self.others.iter().all(|(_, other)| other.is_disjoint(shape))
+                && self
+                    .moving
+                    .iter()
+                    .enumerate()
+                    .all(|(i, (_, other))| unit.contains(&i) || other.is_disjoint(shape))
+        })
+    }
+}
+
+/// Objective scoring a layout, lower costs are better
+pub trait Cost {
+    fn cost(&self, layout: &Layout) -> f64;
+}
+
+/// Maximize the smallest distance between a moving shape and another shape,
+/// the members of a group keeping their own spacing
+pub struct MinSpacing;
+
+impl Cost for MinSpacing {
+    fn cost(&self, layout: &Layout) -> f64 {
+        let moving = layout.moving();
+        let min = layout
+            .units
+            .iter()
+            .flat_map(|unit| moving[unit.clone()].iter().map(move |shape| (unit.end, shape)))
+            .flat_map(|(unit_end, (_, shape))| {
+                moving[unit_end..]
+                    .iter()
+                    .chain(layout.others())
+                    .map(move |(_, other)| shape.distance(other))
+            })
+            .reduce(f32::min);
+
+        -f64::from(min.unwrap_or(0.0))
+    }
+}
+
+/// Keep the moving shapes near the walls of the room
+pub struct NearWalls;
+
+impl Cost for NearWalls {
+    fn cost(&self, layout: &Layout) -> f64 {
+        let Some(room) = layout.plan().room() else {
+            return 0.0;
+        };
+
+        layout
+            .moving()
+            .iter()
+            .map(|(_, shape)| {
+                let (top_left, bottom_right) = shape.bounds();
+                let gaps = [
+                    top_left.x - room.top_left().x,
+                    top_left.y - room.top_left().y,
+                    room.bottom_right().x - bottom_right.x,
+                    room.bottom_right().y - bottom_right.y,
+                ];
+                f64::from(gaps.into_iter().min().unwrap_or(0).max(0))
+            })
+            .sum()
+    }
+}
+
+/// Keep the items of each category together: sum of the distances from the
+/// moving shapes with a category to the center of the shapes of their
+/// category. This is not a walking distance, the distances are measured as
+/// the crow flies between the centers of the bounding boxes
+pub struct CategoryClusters;
+
+impl Cost for CategoryClusters {
+    fn cost(&self, layout: &Layout) -> f64 {
+        let center = |shape: &Shape| {
+            let (top_left, bottom_right) = shape.bounds();
+            (
+                f64::from(top_left.x + bottom_right.x) / 2.0,
+                f64::from(top_left.y + bottom_right.y) / 2.0,
+            )
+        };
+        let category = |shape_id: ShapeId| {
+            layout
+                .plan()
+                .get_metadata(shape_id)
+                .and_then(|metadata| metadata.category.as_deref())
+        };
+
+        // Sum of the centers and number of shapes of each category
+        let mut categories: Vec<(&str, (f64, f64), usize)> = Vec::new();
+        for (shape_id, shape) in layout.moving().iter().chain(layout.others()) {
+            let Some(shape_category) = category(*shape_id) else {
+                continue;
+            };
+            let (x, y) = center(shape);
+            match categories.iter_mut().find(|(name, _, _)| *name == shape_category) {
+                Some((_, (sum_x, sum_y), count)) => {
+                    *sum_x += x;
+                    *sum_y += y;
+                    *count += 1;
+                }
+                None => categories.push((shape_category, (x, y), 1)),
+            }
+        }
+
+        layout
+            .moving()
+            .iter()
+            .filter_map(|(shape_id, shape)| {
+                let shape_category = category(*shape_id)?;
+                let (_, (sum_x, sum_y), count) =
+                    categories.iter().find(|(name, _, _)| *name == shape_category)?;
+                let (x, y) = center(shape);
+                let count = *count as f64;
+                Some(f64::hypot(sum_x / count - x, sum_y / count - y))
+            })
+            .sum()
+    }
+}
+
+/// Total cost of the `layout` for the weighted `costs`
+fn total_cost(layout: &Layout, costs: &[(f64, &dyn Cost)]) -> f64 {
+    costs.iter().map(|(weight, cost)| weight * cost.cost(layout)).sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    pub seed: u64,
+    pub iterations: u32,
+}
+
+/// Best layout found by the optimizer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Optimized {
+    /// New state of the moved shapes
+    pub shapes: Vec<(ShapeId, Shape)>,
+    pub initial_cost: f64,
+    pub cost: f64,
+}
+
+/// Look for a layout of the shapes `shape_ids` of the `plan` minimizing the
+/// weighted `costs`. The same settings always give the same layout
+pub fn optimize(
+    plan: &Plan,
+    shape_ids: &[ShapeId],
+    costs: &[(f64, &dyn Cost)],
+    settings: Settings,
+) -> Optimized {
+    let mut rng = Rng::new(settings.seed);
+    let mut layout = Layout::new(plan, shape_ids);
+    let initial_cost = total_cost(&layout, costs);
+    let mut cost = initial_cost;
+    let mut best = (layout.moving.clone(), cost);
+
+    if !layout.units.is_empty() {
+        for iteration in 0..settings.iterations {
+            let progress = f64::from(iteration) / f64::from(settings.iterations);
+            let temperature = INITIAL_TEMPERATURE * (1.0 - progress);
+            let max_step = (MAX_STEP * (1.0 - progress)).ceil() as i32;
+
+            let unit = layout.units[rng.below(layout.units.len())].clone();
+            let offset = (rng.offset(max_step), rng.offset(max_step)).into();
+            let previous = layout.moving[unit.clone()].to_vec();
+            for (_, shape) in &mut layout.moving[unit.clone()] {
+                shape.translate(offset);
+            }
+
+            if !layout.is_valid(&unit) {
+                layout.moving[unit].clone_from_slice(&previous);
+                continue;
+            }
+
+            let new_cost = total_cost(&layout, costs);
+            let delta = new_cost - cost;
+            let accepted = delta <= 0.0
+                || (temperature > 0.0 && rng.unit() < f64::exp(-delta / temperature));
+            if !accepted {
+                layout.moving[unit].clone_from_slice(&previous);
+                continue;
+            }
+
+            cost = new_cost;
+            if cost < best.1 {
+                best = (layout.moving.clone(), cost);
+            }
+        }
+    }
+
+    Optimized {
+        shapes: best.0,
+        initial_cost,
+        cost: best.1,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::block::BlockMember;
+    use crate::model::metadata::Metadata;
+    use crate::model::room::Room;
+    use crate::model::shape::Rectangle;
+
+    #[test]
+    fn optimizer_spreads_shapes_deterministically() {
+        let mut plan = Plan::default();
+        plan.set_room(Room::new((0, 0).into(), (30, 30).into()));
+        let shape_ids: Vec<_> = [(10, 10), (13, 10), (10, 13)]
+            .into_iter()
+            .map(|pos| {
+                let id = plan.generate_shape_id();
+                plan.add_shape(id, Rectangle::new(pos.into(), 2, 2).into());
+                id
+            })
+            .collect();
+        let costs: [(f64, &dyn Cost); 1] = [(1.0, &MinSpacing)];
+        let settings = Settings {
+            seed: 7,
+            iterations: 500,
+        };
+
+        let optimized = optimize(&plan, &shape_ids, &costs, settings);
+        assert!(optimized.cost < optimized.initial_cost);
+        assert_eq!(optimize(&plan, &shape_ids, &costs, settings), optimized);
+
+        let shapes: Vec<_> = optimized.shapes.iter().map(|(_, shape)| shape.clone()).collect();
+        assert!(plan.are_disjoint(&shapes, &shape_ids));
+        let room = plan.room().unwrap();
+        assert!(shapes.iter().all(|shape| room.contains(shape)));
+    }
+
+    #[test]
+    fn groups_move_together_and_instances_stay() {
+        let mut plan = Plan::default();
+        plan.set_room(Room::new((0, 0).into(), (30, 30).into()));
+        let shape_ids: Vec<_> = [(10, 10), (13, 10), (10, 13), (13, 13)]
+            .into_iter()
+            .map(|pos| {
+                let id = plan.generate_shape_id();
+                plan.add_shape(id, Rectangle::new(pos.into(), 2, 2).into());
+                id
+            })
+            .collect();
+        let group_id = plan.generate_group_id();
+        plan.set_group(shape_ids[0], Some(group_id));
+        plan.set_group(shape_ids[1], Some(group_id));
+        let block_id = plan.generate_block_id();
+        plan.set_block_member(shape_ids[2], BlockMember { block_id, index: 0 });
+
+        let costs: [(f64, &dyn Cost); 1] = [(1.0, &MinSpacing)];
+        let settings = Settings {
+            seed: 3,
+            iterations: 500,
+        };
+        let optimized = optimize(&plan, &shape_ids, &costs, settings);
+        assert!(optimized.cost < optimized.initial_cost);
+        assert!(optimized.shapes.iter().all(|(id, _)| *id != shape_ids[2]));
+
+        let offset = |shape_id: ShapeId| {
+            let (_, shape) = optimized.shapes.iter().find(|(id, _)| *id == shape_id).unwrap();
+            shape.pos() - plan.get_shape(shape_id).unwrap().pos()
+        };
+        assert_eq!(offset(shape_ids[0]), offset(shape_ids[1]));
+    }
+
+    #[test]
+    fn items_of_a_category_get_closer() {
+        let mut plan = Plan::default();
+        plan.set_room(Room::new((0, 0).into(), (40, 40).into()));
+        let shape_ids: Vec<_> = [((2, 2), "desk"), ((30, 30), "desk"), ((2, 30), "storage")]
+            .into_iter()
+            .map(|(pos, category)| {
+                let id = plan.generate_shape_id();
+                plan.add_shape(id, Rectangle::new(pos.into(), 2, 2).into());
+                plan.set_metadata(
+                    id,
+                    Metadata {
+                        name: None,
+                        category: Some(category.into()),
+                    },
+                );
+                id
+            })
+            .collect();
+
+        let costs: [(f64, &dyn Cost); 1] = [(1.0, &CategoryClusters)];
+        let settings = Settings {
+            seed: 5,
+            iterations: 500,
+        };
+        let optimized = optimize(&plan, &shape_ids, &costs, settings);
+        // The lone storage is at the center of its category from the start
+        assert!((optimized.initial_cost - 28.0 * 2f64.sqrt()).abs() < 1e-9);
+        assert!(optimized.cost < optimized.initial_cost / 2.0);
+    }
+}
@@ -0,0 +1,110 @@
+//! Automatic layout of items in a room, with a bottom-left fill heuristic
+
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+
+use super::plan::Plan;
+use super::room::Room;
+use super::shape::Shape;
+use super::vec2::Vec2;
+
+/// Place as many `items` as possible in the `room`, without overlapping the
+/// shapes of the `plan` nor each other. Return the placed shapes along with
+/// the index of their item.
+///
+/// The largest items are placed first, each one at the free spot nearest to
+/// the top of the room and then to its left, which is the bottom-left fill
+/// with the y axis pointing down. Free spots are looked for at the corners
+/// formed by the walls and the right and bottom edges of the other shapes
+pub fn pack(plan: &Plan, room: &Room, items: &[Shape]) -> Vec<(usize, Shape)> {
+    let mut order: Vec<_> = (0..items.len()).collect();
+    order.sort_by_key(|index| {
+        let (top_left, bottom_right) = items[*index].bounds();
+        let size = bottom_right - top_left;
+        Reverse(size.x as i64 * size.y as i64)
+    });
+
+    let obstacles: Vec<_> = plan
+        .iter()
+        .map(|(_, shape)| shape)
+        .filter(|shape| room.overlaps(shape))
+        .cloned()
+        .collect();
+    let mut placed: Vec<(usize, Shape)> = Vec::new();
+
+    for index in order {
+        let item = &items[index];
+        let (item_top_left, item_bottom_right) = item.bounds();
+        let size = item_bottom_right - item_top_left;
+        let max = room.bottom_right() - size;
+
+        let edges: Vec<_> = obstacles
+            .iter()
+            .chain(placed.iter().map(|(_, shape)| shape))
+            .map(|shape| shape.bounds().1)
+            .chain(std::iter::once(room.top_left()))
+            .collect();
+        let xs: BTreeSet<_> = edges
+            .iter()
+            .map(|edge| edge.x)
+            .filter(|x| (room.top_left().x..=max.x).contains(x))
+            .collect();
+        let ys: BTreeSet<_> = edges
+            .iter()
+            .map(|edge| edge.y)
+            .filter(|y| (room.top_left().y..=max.y).contains(y))
+            .collect();
+
+        let spot = ys
+            .iter()
+            .flat_map(|y| xs.iter().map(|x| Vec2::new(*x, *y)))
+            .map(|pos| {
+                let mut shape = item.clone();
+                shape.translate(pos - item_top_left);
+                shape
+            })
+            .find(|shape| {
+                obstacles
+                    .iter()
+                    .chain(placed.iter().map(|(_, shape)| shape))
+                    .all(|other| other.is_disjoint(shape))
+            });
+
+        if let Some(shape) = spot {
+            placed.push((index, shape));
+        }
+    }
+
+    placed
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::shape::{Circle, Rectangle};
+
+    #[test]
+    fn pack_reports_items_left_out() {
+        let mut plan = Plan::default();
+        let pillar = plan.generate_shape_id();
+        plan.add_shape(pillar, Rectangle::new((8, 0).into(), 2, 2).into());
+        let room = Room::new((0, 0).into(), (10, 6).into()).unwrap();
+
+        let desk: Shape = Rectangle::new((0, 0).into(), 4, 2).into();
+        let lamp: Shape = Circle::new((0, 0).into(), 1).into();
+        // Only six desks fit around the pillar, the lamp goes next to it
+        let mut items = vec![desk; 7];
+        items.insert(0, lamp);
+
+        let placed = pack(&plan, &room, &items);
+        let shapes: Vec<_> = placed.iter().map(|(_, shape)| shape.clone()).collect();
+        assert_eq!(placed.len(), 7);
+        assert!(placed.iter().any(|(index, _)| *index == 0));
+        assert!(shapes.iter().all(|shape| plan.is_disjoint(shape, None)));
+        assert!(plan.are_disjoint(&shapes, &[]));
+        assert!(shapes.iter().all(|shape| {
+            let (top_left, bottom_right) = shape.bounds();
+            top_left.x >= 0 && top_left.y >= 0 && bottom_right.x <= 10 && bottom_right.y <= 6
+        }));
+    }
+}
@@ -1,23 +1,73 @@
 use std::num::NonZeroU32;
 
-use super::shape::Shape;
+use serde::{Deserialize, Serialize};
+
+use super::block::{Block, BlockMember};
+use super::dimension::Dimension;
+use super::metadata::Metadata;
+use super::room::Room;
+use super::shape::{self, Shape};
 use super::vec2::Vec2;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(test, derive(Hash))]
 pub struct ShapeId(NonZeroU32);
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DimensionId(NonZeroU32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockId(NonZeroU32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupId(NonZeroU32);
+
+/// Distance from the target up to which free spots are looked for
+const FREE_SPOT_SEARCH_RADIUS: i32 = 100;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Plan {
     next_id: NonZeroU32,
     shapes: Vec<(ShapeId, Shape)>,
+    next_dimension_id: NonZeroU32,
+    dimensions: Vec<(DimensionId, Dimension)>,
+    #[serde(default)]
+    metadata: Vec<(ShapeId, Metadata)>,
+    #[serde(default = "first_id")]
+    next_block_id: NonZeroU32,
+    #[serde(default)]
+    blocks: Vec<(BlockId, Block)>,
+    #[serde(default)]
+    block_members: Vec<(ShapeId, BlockMember)>,
+    #[serde(default = "first_id")]
+    next_group_id: NonZeroU32,
+    #[serde(default)]
+    groups: Vec<(ShapeId, GroupId)>,
+    #[serde(default)]
+    room: Option<Room>,
+    #[serde(default)]
+    exits: Vec<Vec2>,
+}
+
+fn first_id() -> NonZeroU32 {
+    NonZeroU32::new(1).unwrap()
 }
 
 impl Default for Plan {
     fn default() -> Self {
         Self {
-            next_id: NonZeroU32::new(1).unwrap(),
+            next_id: first_id(),
             shapes: Default::default(),
+            next_dimension_id: first_id(),
+            dimensions: Default::default(),
+            metadata: Default::default(),
+            next_block_id: first_id(),
+            blocks: Default::default(),
+            block_members: Default::default(),
+            next_group_id: first_id(),
+            groups: Default::default(),
+            room: None,
+            exits: Vec::new(),
         }
     }
 }
@@ -43,29 +93,257 @@ impl Plan {
         self.shapes.iter().find(|(_, shape)| shape.contains(point))
     }
 
+    /// Remove the shape along with its metadata, its link to a block and its group
     pub fn remove_shape(&mut self, shape_id: ShapeId) {
         self.shapes.retain(|(id, _)| *id != shape_id);
+        self.metadata.retain(|(id, _)| *id != shape_id);
+        self.remove_block_member(shape_id);
+        self.set_group(shape_id, None);
     }
 
+    /// Replace the shape, its metadata, its link to a block and its group are kept
     pub fn replace_shape(&mut self, shape_id: ShapeId, shape: Shape) {
-        self.remove_shape(shape_id);
+        self.shapes.retain(|(id, _)| *id != shape_id);
         self.shapes.push((shape_id, shape));
     }
 
+    pub fn set_metadata(&mut self, shape_id: ShapeId, metadata: Metadata) {
+        self.metadata.retain(|(id, _)| *id != shape_id);
+        self.metadata.push((shape_id, metadata));
+    }
+
+    pub fn get_metadata(&self, shape_id: ShapeId) -> Option<&Metadata> {
+        self.metadata
+            .iter()
+            .find_map(|(id, metadata)| Some(metadata).filter(|_| shape_id == *id))
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &(ShapeId, Shape)> {
         self.shapes.iter()
     }
 
+    pub fn generate_block_id(&mut self) -> BlockId {
+        let id = self.next_block_id;
+        self.next_block_id = self.next_block_id.checked_add(1).unwrap();
+        BlockId(id)
+    }
+
+    pub fn add_block(&mut self, block_id: BlockId, block: Block) {
+        self.blocks.push((block_id, block));
+    }
+
+    pub fn get_block(&self, block_id: BlockId) -> Option<&Block> {
+        self.blocks
+            .iter()
+            .find_map(|(id, block)| Some(block).filter(|_| block_id == *id))
+    }
+
+    /// Remove the block definition, its instances are left as plain shapes
+    pub fn remove_block(&mut self, block_id: BlockId) {
+        self.blocks.retain(|(id, _)| *id != block_id);
+        self.block_members
+            .retain(|(_, member)| member.block_id != block_id);
+    }
+
+    /// Replace the definition of the block, without updating its instances
+    pub fn replace_block(&mut self, block_id: BlockId, block: Block) {
+        if let Some((_, old)) = self.blocks.iter_mut().find(|(id, _)| *id == block_id) {
+            *old = block;
+        }
+    }
+
+    pub fn blocks(&self) -> impl Iterator<Item = &(BlockId, Block)> {
+        self.blocks.iter()
+    }
+
+    /// Link the shape to a shape of a block definition
+    pub fn set_block_member(&mut self, shape_id: ShapeId, member: BlockMember) {
+        self.remove_block_member(shape_id);
+        self.block_members.push((shape_id, member));
+    }
+
+    pub fn get_block_member(&self, shape_id: ShapeId) -> Option<&BlockMember> {
+        self.block_members
+            .iter()
+            .find_map(|(id, member)| Some(member).filter(|_| shape_id == *id))
+    }
+
+    pub fn remove_block_member(&mut self, shape_id: ShapeId) {
+        self.block_members.retain(|(id, _)| *id != shape_id);
+    }
+
+    /// Iterate over the shapes of all the instances of the block
+    /// along with the index of their shape in the definition
+    pub fn block_members(&self, block_id: BlockId) -> impl Iterator<Item = (ShapeId, usize)> + '_ {
+        self.block_members
+            .iter()
+            .filter(move |(_, member)| member.block_id == block_id)
+            .map(|(id, member)| (*id, member.index))
+    }
+
+    pub fn generate_group_id(&mut self) -> GroupId {
+        let id = self.next_group_id;
+        self.next_group_id = self.next_group_id.checked_add(1).unwrap();
+        GroupId(id)
+    }
+
+    /// Put the shape in the group, or take it out of its group with `None`
+    pub fn set_group(&mut self, shape_id: ShapeId, group_id: Option<GroupId>) {
+        self.groups.retain(|(id, _)| *id != shape_id);
+        if let Some(group_id) = group_id {
+            self.groups.push((shape_id, group_id));
+        }
+    }
+
+    pub fn set_room(&mut self, room: Option<Room>) {
+        self.room = room;
+    }
+
+    pub fn room(&self) -> Option<&Room> {
+        self.room.as_ref()
+    }
+
+    pub fn set_exits(&mut self, exits: Vec<Vec2>) {
+        self.exits = exits;
+    }
+
+    pub fn exits(&self) -> &[Vec2] {
+        &self.exits
+    }
+
+    pub fn get_group(&self, shape_id: ShapeId) -> Option<GroupId> {
+        self.groups
+            .iter()
+            .find_map(|(id, group_id)| Some(*group_id).filter(|_| shape_id == *id))
+    }
+
+    /// The shape along with the other members of its group. Shapes
+    /// of a group are selected together
+    pub fn group_of(&self, shape_id: ShapeId) -> Vec<ShapeId> {
+        match self.get_group(shape_id) {
+            Some(group_id) => self
+                .groups
+                .iter()
+                .filter(|(_, group)| *group == group_id)
+                .map(|(id, _)| *id)
+                .collect(),
+            None => vec![shape_id],
+        }
+    }
+
+    pub fn generate_dimension_id(&mut self) -> DimensionId {
+        let id = self.next_dimension_id;
+        self.next_dimension_id = self.next_dimension_id.checked_add(1).unwrap();
+        DimensionId(id)
+    }
+
+    pub fn add_dimension(&mut self, dimension_id: DimensionId, dimension: Dimension) {
+        self.dimensions.push((dimension_id, dimension));
+    }
+
+    pub fn get_dimension(&self, dimension_id: DimensionId) -> Option<&Dimension> {
+        self.dimensions
+            .iter()
+            .find_map(|(id, dimension)| Some(dimension).filter(|_| dimension_id == *id))
+    }
+
+    pub fn remove_dimension(&mut self, dimension_id: DimensionId) {
+        self.dimensions.retain(|(id, _)| *id != dimension_id);
+    }
+
+    /// Iterate over the dimensions along with the shape they are attached to.
+    /// Dimensions attached to a shape missing from the plan are skipped
+    pub fn dimensions(&self) -> impl Iterator<Item = (DimensionId, &Dimension, &Shape)> {
+        self.dimensions.iter().filter_map(|(id, dimension)| {
+            let shape = self.get_shape(dimension.shape_id())?;
+            Some((*id, dimension, shape))
+        })
+    }
+
+    /// Check if the `shapes` are disjoint from each other and from the rest
+    /// of the shapes excepts the ones in `ignore`
+    pub fn are_disjoint(&self, shapes: &[Shape], ignore: &[ShapeId]) -> bool {
+        shapes.iter().enumerate().all(|(i, shape)| {
+            shapes[..i].iter().all(|other| other.is_disjoint(shape))
+                && self
+                    .shapes
+                    .iter()
+                    .filter(|(id, _)| !ignore.contains(id))
+                    .all(|(_, other)| other.is_disjoint(shape))
+        })
+    }
+
+    /// Find the position nearest to `target` where the `shapes` can be placed
+    /// once translated by it. Positions are looked for ring by ring around the
+    /// target, leaving out the ones moving the shapes to negative coordinates
+    pub fn first_free_spot(&self, shapes: &[Shape], target: Vec2) -> Option<Vec2> {
+        let (top_left, _) = shape::bounding_box(shapes)?;
+
+        (0..=FREE_SPOT_SEARCH_RADIUS)
+            .flat_map(|radius| {
+                (-radius..=radius).flat_map(move |dy| {
+                    (-radius..=radius)
+                        .filter(move |dx| dx.abs() == radius || dy.abs() == radius)
+                        .map(move |dx| target + Vec2::new(dx, dy))
+                })
+            })
+            .filter(|offset| {
+                let top_left = top_left + *offset;
+                top_left.x >= 0 && top_left.y >= 0
+            })
+            .find(|offset| {
+                let shapes: Vec<_> = shapes
+                    .iter()
+                    .map(|shape| {
+                        let mut shape = shape.clone();
+                        shape.translate(*offset);
+                        shape
+                    })
+                    .collect();
+                self.are_disjoint(&shapes, &[])
+            })
+    }
+
+    /// Iterate over the shapes entirely inside the area between the two corners
+    pub fn shapes_in_area(
+        &self,
+        corner1: Vec2,
+        corner2: Vec2,
+    ) -> impl Iterator<Item = &(ShapeId, Shape)> {
+        let min = Vec2::new(corner1.x.min(corner2.x), corner1.y.min(corner2.y));
+        let max = Vec2::new(corner1.x.max(corner2.x), corner1.y.max(corner2.y));
+
+        self.shapes.iter().filter(move |(_, shape)| {
+            let (top_left, bottom_right) = shape.bounds();
+            top_left.x >= min.x
+                && top_left.y >= min.y
+                && bottom_right.x <= max.x
+                && bottom_right.y <= max.y
+        })
+    }
+
     /// Check if the `shape` is disjoint from the rest of the shapes
     /// exepts the one with the id `ignore`
     pub fn is_disjoint(&self, shape: &Shape, ignore: Option<ShapeId>) -> bool {
+        self.conflicting_shapes(shape, ignore.as_slice()).next().is_none()
+    }
+
+    /// Return the ids of the shapes overlapping `shape`
+    /// exepts the ones in `ignore`
+    pub fn conflicts(&self, shape: &Shape, ignore: &[ShapeId]) -> Vec<ShapeId> {
+        self.conflicting_shapes(shape, ignore).collect()
+    }
+
+    fn conflicting_shapes<'a>(
+        &'a self,
+        shape: &'a Shape,
+        ignore: &'a [ShapeId],
+    ) -> impl Iterator<Item = ShapeId> + 'a {
         self.shapes
             .iter()
-            .filter_map(|(id, shape)| match ignore {
-                Some(ignore_id) if *id == ignore_id => None,
-                _ => Some(shape),
-            })
-            .all(|s| s.is_disjoint(shape))
+            .filter(move |(id, _)| !ignore.contains(id))
+            .filter(|(_, s)| !s.is_disjoint(shape))
+            .map(|(id, _)| *id)
     }
 }
 
@@ -74,6 +352,7 @@ mod test {
     use std::collections::HashSet;
 
     use super::*;
+    use crate::model::shape::{Circle, Rectangle};
 
     #[test]
     fn id_all_unique() {
@@ -87,4 +366,58 @@ mod test {
 
         assert_eq!(total_id_count, unique_id_count);
     }
+
+    #[test]
+    fn conflicts_lists_overlapping_shapes() {
+        let mut plan = Plan::default();
+        let (id1, id2, id3) = (
+            plan.generate_shape_id(),
+            plan.generate_shape_id(),
+            plan.generate_shape_id(),
+        );
+        plan.add_shape(id1, Rectangle::new((0, 0).into(), 4, 4).into());
+        plan.add_shape(id2, Circle::new((10, 2).into(), 2).into());
+        plan.add_shape(id3, Rectangle::new((20, 20).into(), 2, 2).into());
+
+        let shape: Shape = Rectangle::new((2, 1).into(), 8, 2).into();
+
+        assert_eq!(plan.conflicts(&shape, &[]), vec![id1, id2]);
+        assert_eq!(plan.conflicts(&shape, &[id1]), vec![id2]);
+        assert!(!plan.is_disjoint(&shape, None));
+    }
+
+    #[test]
+    fn metadata_follows_shape() {
+        let mut plan = Plan::default();
+        let id = plan.generate_shape_id();
+        let metadata = Metadata {
+            name: Some("Desk".into()),
+            category: None,
+        };
+        plan.add_shape(id, Rectangle::new((0, 0).into(), 4, 2).into());
+        plan.set_metadata(id, metadata.clone());
+
+        plan.replace_shape(id, Rectangle::new((5, 5).into(), 4, 2).into());
+        assert_eq!(plan.get_metadata(id), Some(&metadata));
+
+        plan.remove_shape(id);
+        assert_eq!(plan.get_metadata(id), None);
+    }
+
+    #[test]
+    fn first_free_spot_avoids_shapes() {
+        let mut plan = Plan::default();
+        let id = plan.generate_shape_id();
+        plan.add_shape(id, Rectangle::new((0, 0).into(), 4, 4).into());
+
+        let shapes: [Shape; 1] = [Rectangle::new((0, 0).into(), 2, 2).into()];
+        assert_eq!(plan.first_free_spot(&shapes, (10, 10).into()), Some((10, 10).into()));
+
+        let spot = plan.first_free_spot(&shapes, (1, 1).into()).unwrap();
+        let mut shape = shapes[0].clone();
+        shape.translate(spot);
+        assert!(plan.is_disjoint(&shape, None));
+        assert!(spot.x >= 0 && spot.y >= 0);
+        assert!(spot.exact_distance(&(1, 1).into()) <= 6.0);
+    }
 }
@@ -0,0 +1,321 @@
+//! Occupancy report of a plan: the area taken by its shapes, the free space
+//! left in its room and the largest free spots
+
+use std::fmt::Write;
+
+use super::grid::{self, Grid};
+use super::plan::Plan;
+use super::room::Room;
+use super::shape::{Rectangle, Shape};
+use super::vec2::Vec2;
+
+/// Largest number of cells looked at for the largest empty rectangle,
+/// larger rooms get coarser cells
+const MAX_CELLS: usize = 250_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryStats {
+    /// `None` for the shapes without a category
+    pub category: Option<String>,
+    pub count: usize,
+    pub area: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub shape_count: usize,
+    /// Total area of the shapes
+    pub shape_area: f64,
+    pub room: Option<Room>,
+    /// Area of the room not taken by the shapes
+    pub free_area: Option<f64>,
+    /// Shapes of each category, sorted by category with the shapes
+    /// without a category last
+    pub categories: Vec<CategoryStats>,
+    /// Largest empty rectangle of the room, aligned with the axes
+    pub largest_rectangle: Option<Rectangle>,
+    /// Center and radius of the largest empty circle of the room
+    pub largest_circle: Option<(Vec2, f32)>,
+}
+
+impl Report {
+    /// Report on the `plan`. The free space is only measured in its room,
+    /// the rest of the plan has no boundary
+    pub fn new(plan: &Plan) -> Self {
+        let mut categories: Vec<CategoryStats> = Vec::new();
+        for (id, shape) in plan.iter() {
+            let category = plan
+                .get_metadata(*id)
+                .and_then(|metadata| metadata.category.clone());
+            match categories.iter_mut().find(|stats| stats.category == category) {
+                Some(stats) => {
+                    stats.count += 1;
+                    stats.area += shape.area();
+                }
+                None => categories.push(CategoryStats {
+                    category,
+                    count: 1,
+                    area: shape.area(),
+                }),
+            }
+        }
+        categories.sort_by(|a, b| {
+            (a.category.is_none(), &a.category).cmp(&(b.category.is_none(), &b.category))
+        });
+
+        let room = plan.room().copied();
+        let free_area = room.map(|room| {
+            let taken: f64 = plan.iter().map(|(_, shape)| area_in_room(shape, &room)).sum();
+            room_area(&room) - taken
+        });
+
+        Self {
+            shape_count: plan.iter().count(),
+            shape_area: plan.iter().map(|(_, shape)| shape.area()).sum(),
+            room,
+            free_area,
+            categories,
+            largest_rectangle: room.and_then(|room| largest_rectangle(plan, &room)),
+            largest_circle: room.and_then(|_| largest_circle(plan)),
+        }
+    }
+
+    /// Report as CSV, with a row per statistic. The position and size of
+    /// the rooms and free spots are the ones of their bounding boxes
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("item,count,area,x,y,width,height\n");
+        let mut row = |item: &str, count: Option<usize>, area: f64, bounds: Option<[f32; 4]>| {
+            let count = count.map(|count| count.to_string()).unwrap_or_default();
+            let bounds = match bounds {
+                Some(bounds) => bounds.map(|value| value.to_string()).join(","),
+                None => ",,,".into(),
+            };
+            let _ = writeln!(csv, "{},{count},{area:.2},{bounds}", csv_field(item));
+        };
+
+        row("Shapes", Some(self.shape_count), self.shape_area, None);
+        if let (Some(room), Some(free_area)) = (self.room, self.free_area) {
+            let size = room.bottom_right() - room.top_left();
+            let bounds = [room.top_left().x, room.top_left().y, size.x, size.y].map(|v| v as f32);
+            row("Room", None, room_area(&room), Some(bounds));
+            row("Free space", None, free_area, None);
+        }
+        for stats in &self.categories {
+            let item = match &stats.category {
+                Some(category) => format!("Category {category}"),
+                None => "Uncategorized".into(),
+            };
+            row(&item, Some(stats.count), stats.area, None);
+        }
+        if let Some(rect) = &self.largest_rectangle {
+            let bounds = [rect.pos().x, rect.pos().y, rect.width() as i32, rect.height() as i32];
+            let area = Shape::from(rect.clone()).area();
+            row("Largest empty rectangle", None, area, Some(bounds.map(|v| v as f32)));
+        }
+        if let Some((center, radius)) = self.largest_circle {
+            let bounds = [
+                center.x as f32 - radius,
+                center.y as f32 - radius,
+                2.0 * radius,
+                2.0 * radius,
+            ];
+            let area = std::f64::consts::PI * (radius as f64).powi(2);
+            row("Largest inscribed circle", None, area, Some(bounds));
+        }
+
+        csv
+    }
+}
+
+fn room_area(room: &Room) -> f64 {
+    let size = room.bottom_right() - room.top_left();
+    size.x as f64 * size.y as f64
+}
+
+/// Area of the part of the `shape` inside of the `room`
+fn area_in_room(shape: &Shape, room: &Room) -> f64 {
+    if room.contains(shape) {
+        return shape.area();
+    }
+    let (min, max) = (room.top_left(), room.bottom_right());
+
+    match shape {
+        Shape::Rectangle(_) => {
+            let (top_left, bottom_right) = shape.bounds();
+            let width = bottom_right.x.min(max.x) - top_left.x.max(min.x);
+            let height = bottom_right.y.min(max.y) - top_left.y.max(min.y);
+            width.max(0) as f64 * height.max(0) as f64
+        }
+        Shape::Circle(circle) => {
+            // Inclusion-exclusion of the quadrants at the corners of the room,
+            // relative to the center of the circle
+            let (cx, cy) = (circle.pos().x as f64, circle.pos().y as f64);
+            let radius = circle.radius() as f64;
+            let (x0, y0) = (min.x as f64 - cx, min.y as f64 - cy);
+            let (x1, y1) = (max.x as f64 - cx, max.y as f64 - cy);
+            let quadrant = |x, y| quadrant_area(radius, x, y);
+            (quadrant(x0, y0) - quadrant(x1, y0) - quadrant(x0, y1) + quadrant(x1, y1)).max(0.0)
+        }
+    }
+}
+
+/// Area of the part of the circle of `radius` centered on the origin
+/// where both coordinates are larger than `x` and `y`
+fn quadrant_area(radius: f64, x: f64, y: f64) -> f64 {
+    if radius <= 0.0 {
+        return 0.0;
+    }
+    // Half height of the circle at `t`, and its antiderivative
+    let half = |t: f64| (radius * radius - t * t).max(0.0).sqrt();
+    let integral = |t: f64| {
+        let t = t.clamp(-radius, radius);
+        (t * half(t) + radius * radius * (t / radius).asin()) / 2.0
+    };
+    let between = |from: f64, to: f64| {
+        if from < to {
+            integral(to) - integral(from)
+        } else {
+            0.0
+        }
+    };
+
+    // Columns where the line at `y` crosses the circle, which is cut there,
+    // while the columns further out are whole when the line is below the center
+    let x = x.clamp(-radius, radius);
+    let cut = half(y);
+    let mut area = between(x.max(-cut), cut) - y * (cut - x.max(-cut)).max(0.0);
+    if y < 0.0 {
+        area += 2.0 * (between(x, -cut) + between(x.max(cut), radius));
+    }
+    area
+}
+
+/// Largest rectangle of the `room` touching none of the shapes of the
+/// `plan`, made of square cells
+fn largest_rectangle(plan: &Plan, room: &Room) -> Option<Rectangle> {
+    let size = room.bottom_right() - room.top_left();
+    let area = size.x as f64 * size.y as f64;
+    let step = (area / MAX_CELLS as f64).sqrt().ceil().max(1.0) as i32;
+    let (columns, rows) = ((size.x / step) as usize, (size.y / step) as usize);
+    let cell_at = |column: usize, row: usize| {
+        let pos = room.top_left() + Vec2::new(column as i32 * step, row as i32 * step);
+        Shape::from(Rectangle::new(pos, step as u32, step as u32))
+    };
+
+    // Number of free cells above each cell of the row, with the classic
+    // largest rectangle in a histogram for each row
+    let mut heights = vec![0; columns];
+    let mut best: Option<(usize, usize, usize, usize)> = None;
+    for row in 0..rows {
+        for (column, height) in heights.iter_mut().enumerate() {
+            let cell = cell_at(column, row);
+            let free = plan.iter().all(|(_, shape)| shape.is_disjoint(&cell));
+            *height = if free { *height + 1 } else { 0 };
+        }
+
+        let mut stack: Vec<usize> = Vec::new();
+        for column in 0..=columns {
+            let height = heights.get(column).copied().unwrap_or(0);
+            while let Some(&top) = stack.last() {
+                if heights[top] < height {
+                    break;
+                }
+                stack.pop();
+                let first = stack.last().map_or(0, |previous| previous + 1);
+                let (width, top_height) = (column - first, heights[top]);
+                if best.is_none_or(|(_, _, w, h)| width * top_height > w * h) {
+                    best = Some((first, row + 1 - top_height, width, top_height));
+                }
+            }
+            stack.push(column);
+        }
+    }
+
+    let (column, row, width, height) = best.filter(|(_, _, w, h)| w * h > 0)?;
+    let pos = room.top_left() + Vec2::new(column as i32 * step, row as i32 * step);
+    Some(Rectangle::new(
+        pos,
+        (width as i32 * step) as u32,
+        (height as i32 * step) as u32,
+    ))
+}
+
+/// Point of the free space of the `plan` farthest from its shapes and
+/// the walls of its room, with that distance
+fn largest_circle(plan: &Plan) -> Option<(Vec2, f32)> {
+    let grid = Grid::for_plan(plan)?;
+    let clearance = grid::clearance(plan, &grid);
+    let (index, radius) = clearance
+        .into_iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+    (radius > 0.0).then(|| (grid.point(index), radius))
+}
+
+/// Quote a field of a CSV row when needed
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::metadata::Metadata;
+    use crate::model::shape::Circle;
+
+    #[test]
+    fn report_measures_free_space() {
+        let mut plan = Plan::default();
+        for pos in [(0, 0), (16, 0)] {
+            let id = plan.generate_shape_id();
+            plan.add_shape(id, Rectangle::new(pos.into(), 4, 2).into());
+            let metadata = Metadata {
+                name: None,
+                category: Some("desk, small".into()),
+            };
+            plan.set_metadata(id, metadata);
+        }
+        let table = plan.generate_shape_id();
+        plan.add_shape(table, Circle::new((10, 5).into(), 2).into());
+
+        let report = Report::new(&plan);
+        let circle_area = std::f64::consts::PI * 4.0;
+        assert_eq!(report.shape_count, 3);
+        assert!((report.shape_area - (16.0 + circle_area)).abs() < 1e-9);
+        assert_eq!(report.free_area, None);
+        assert_eq!(report.largest_rectangle, None);
+        assert_eq!(report.categories.len(), 2);
+        assert_eq!(report.categories[0].count, 2);
+        assert_eq!(report.categories[1].category, None);
+
+        plan.set_room(Room::new((0, 0).into(), (20, 10).into()));
+        let report = Report::new(&plan);
+        let free_area = report.free_area.unwrap();
+        assert!((free_area - (200.0 - 16.0 - circle_area)).abs() < 1e-6);
+        let rect = report.largest_rectangle.as_ref().unwrap();
+        assert_eq!(rect.width() * rect.height(), 64);
+        let (_, radius) = report.largest_circle.unwrap();
+        assert!((4.0..5.0).contains(&radius));
+
+        let csv = report.to_csv();
+        assert!(csv.starts_with("item,count,area,x,y,width,height\n"));
+        assert!(csv.contains("Room,,200.00,0,0,20,10\n"));
+        assert!(csv.contains("\"Category desk, small\",2,16.00,,,,\n"));
+        assert!(csv.contains("Uncategorized,1,12.57,,,,\n"));
+
+        // Shapes crossing the walls only count for their part in the room
+        let corner = plan.generate_shape_id();
+        plan.add_shape(corner, Circle::new((0, 10).into(), 2).into());
+        let report = Report::new(&plan);
+        assert!((report.free_area.unwrap() - (free_area - circle_area / 4.0)).abs() < 1e-9);
+        plan.remove_shape(corner);
+        let wall = plan.generate_shape_id();
+        plan.add_shape(wall, Circle::new((20, 5).into(), 2).into());
+        let report = Report::new(&plan);
+        assert!((report.free_area.unwrap() - (free_area - circle_area / 2.0)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,51 @@
+//! Boundary of the room the shapes of a plan are laid out in
+
+use serde::{Deserialize, Serialize};
+
+use super::shape::Shape;
+use super::vec2::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Room {
+    top_left: Vec2,
+    bottom_right: Vec2,
+}
+
+impl Room {
+    /// Room spanning between two opposite corners, `None` if it has no area
+    pub fn new(corner1: Vec2, corner2: Vec2) -> Option<Self> {
+        let top_left = Vec2::new(corner1.x.min(corner2.x), corner1.y.min(corner2.y));
+        let bottom_right = Vec2::new(corner1.x.max(corner2.x), corner1.y.max(corner2.y));
+
+        (top_left.x < bottom_right.x && top_left.y < bottom_right.y).then_some(Self {
+            top_left,
+            bottom_right,
+        })
+    }
+
+    pub fn top_left(&self) -> Vec2 {
+        self.top_left
+    }
+
+    pub fn bottom_right(&self) -> Vec2 {
+        self.bottom_right
+    }
+
+    /// Whether the `shape` is entirely inside of the room
+    pub fn contains(&self, shape: &Shape) -> bool {
+        let (top_left, bottom_right) = shape.bounds();
+        top_left.x >= self.top_left.x
+            && top_left.y >= self.top_left.y
+            && bottom_right.x <= self.bottom_right.x
+            && bottom_right.y <= self.bottom_right.y
+    }
+
+    /// Whether the `shape` overlaps the inside of the room
+    pub fn overlaps(&self, shape: &Shape) -> bool {
+        let (top_left, bottom_right) = shape.bounds();
+        top_left.x < self.bottom_right.x
+            && top_left.y < self.bottom_right.y
+            && bottom_right.x > self.top_left.x
+            && bottom_right.y > self.top_left.y
+    }
+}
@@ -4,15 +4,84 @@ pub use rectangle::*;
 pub mod circle;
 pub use circle::*;
 
+use serde::{Deserialize, Serialize};
+
 use super::vec2::Vec2;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Shape {
     Circle(Circle),
     Rectangle(Rectangle),
 }
 
+/// Direction in which shapes are mirrored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Flip {
+    /// Left and right are swapped, about a vertical axis
+    Horizontal,
+    /// Top and bottom are swapped, about a horizontal axis
+    Vertical,
+}
+
+/// Top left and bottom right corners of the bounding box of all the `shapes`,
+/// `None` when there is no shape
+pub fn bounding_box<'a>(shapes: impl IntoIterator<Item = &'a Shape>) -> Option<(Vec2, Vec2)> {
+    shapes
+        .into_iter()
+        .map(Shape::bounds)
+        .reduce(|(min, max), (top_left, bottom_right)| {
+            (
+                Vec2::new(min.x.min(top_left.x), min.y.min(top_left.y)),
+                Vec2::new(max.x.max(bottom_right.x), max.y.max(bottom_right.y)),
+            )
+        })
+}
+
+/// Whether the `shapes` lie in the positive coordinates the plans are drawn in
+pub fn are_in_plan<'a>(shapes: impl IntoIterator<Item = &'a Shape>) -> bool {
+    bounding_box(shapes).is_none_or(|(top_left, _)| top_left.x >= 0 && top_left.y >= 0)
+}
+
 impl Shape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Circle(_) => "Circle",
+            Shape::Rectangle(_) => "Rectangle",
+        }
+    }
+
+    /// Top left corner of a rectangle or center of a circle
+    pub fn pos(&self) -> Vec2 {
+        match self {
+            Shape::Circle(circle) => circle.pos(),
+            Shape::Rectangle(rect) => rect.pos(),
+        }
+    }
+
+    pub fn area(&self) -> f64 {
+        match self {
+            Shape::Circle(circle) => std::f64::consts::PI * (circle.radius() as f64).powi(2),
+            Shape::Rectangle(rect) => rect.width() as f64 * rect.height() as f64,
+        }
+    }
+
+    /// Top left and bottom right corners of the bounding box of the shape
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        match self {
+            Shape::Circle(circle) => {
+                let radius = circle.radius() as i32;
+                (
+                    circle.pos() - Vec2::new(radius, radius),
+                    circle.pos() + Vec2::new(radius, radius),
+                )
+            }
+            Shape::Rectangle(rect) => (
+                rect.pos(),
+                rect.pos() + Vec2::new(rect.width() as i32, rect.height() as i32),
+            ),
+        }
+    }
+
     pub fn move_to(&mut self, to: Vec2) {
         match self {
             Shape::Circle(circle) => circle.move_to(to),
@@ -20,6 +89,23 @@ impl Shape {
         }
     }
 
+    pub fn translate(&mut self, offset: Vec2) {
+        self.move_to(self.pos() + offset);
+    }
+
+    /// Mirror the shape about an axis. `axis` is twice the coordinate of the
+    /// axis, so that it can go through the middle of a unit
+    pub fn mirror(&mut self, flip: Flip, axis: i32) {
+        // Circles and rectangles are symmetric, so their mirror image is
+        // the same shape moved to the other side of the axis
+        let (top_left, bottom_right) = self.bounds();
+        let offset = match flip {
+            Flip::Horizontal => Vec2::new(axis - top_left.x - bottom_right.x, 0),
+            Flip::Vertical => Vec2::new(0, axis - top_left.y - bottom_right.y),
+        };
+        self.translate(offset);
+    }
+
     pub fn contains(&self, point: &Vec2) -> bool {
         match self {
             Shape::Circle(circle) => circle.contains(point),
@@ -42,6 +128,23 @@ impl Shape {
         }
     }
 
+    /// Nearest distance between the borders of the two shapes,
+    /// 0 when they overlap
+    pub fn distance(&self, other: &Shape) -> f32 {
+        match (self, other) {
+            (Shape::Circle(circle1), Shape::Circle(circle2)) => {
+                Self::distance_circles(circle1, circle2)
+            }
+            (Shape::Rectangle(rect1), Shape::Rectangle(rect2)) => {
+                Self::distance_rects(rect1, rect2)
+            }
+            (Shape::Circle(circle), Shape::Rectangle(rect))
+            | (Shape::Rectangle(rect), Shape::Circle(circle)) => {
+                Self::distance_rect_circle(rect, circle)
+            }
+        }
+    }
+
     fn is_disjoint_rects(rect1: &Rectangle, rect2: &Rectangle) -> bool {
         let x_overlap = i32::min(
             rect1.pos().x + rect1.width() as i32,
@@ -80,6 +183,46 @@ impl Shape {
         circle.pos().distance(&nearest_point) >= circle.radius()
     }
 
+    fn distance_rects(rect1: &Rectangle, rect2: &Rectangle) -> f32 {
+        let x_gap = i32::max(rect1.pos().x, rect2.pos().x)
+            - i32::min(
+                rect1.pos().x + rect1.width() as i32,
+                rect2.pos().x + rect2.width() as i32,
+            );
+
+        let y_gap = i32::max(rect1.pos().y, rect2.pos().y)
+            - i32::min(
+                rect1.pos().y + rect1.height() as i32,
+                rect2.pos().y + rect2.height() as i32,
+            );
+
+        f32::hypot(x_gap.max(0) as f32, y_gap.max(0) as f32)
+    }
+
+    fn distance_circles(circle1: &Circle, circle2: &Circle) -> f32 {
+        let dist = circle1.pos().exact_distance(&circle2.pos());
+
+        f32::max(dist - (circle1.radius() + circle2.radius()) as f32, 0.0)
+    }
+
+    fn distance_rect_circle(rect: &Rectangle, circle: &Circle) -> f32 {
+        let nearest_x = i32::clamp(
+            circle.pos().x,
+            rect.pos().x,
+            rect.pos().x + rect.width() as i32,
+        );
+
+        let nearest_y = i32::clamp(
+            circle.pos().y,
+            rect.pos().y,
+            rect.pos().y + rect.height() as i32,
+        );
+
+        let nearest_point = (nearest_x, nearest_y).into();
+        let dist = circle.pos().exact_distance(&nearest_point);
+
+        f32::max(dist - circle.radius() as f32, 0.0)
+    }
 }
 
 impl From<Rectangle> for Shape {
@@ -151,4 +294,51 @@ mod test {
         assert!(!r1.is_disjoint(&r2));
         assert!(!r2.is_disjoint(&r1));
     }
+
+    #[test]
+    fn distance_rect_rect() {
+        let r1: Shape = Rectangle::new((0, 0).into(), 2, 2).into();
+        let r2: Shape = Rectangle::new((5, 6).into(), 1, 3).into();
+        let r3: Shape = Rectangle::new((1, 10).into(), 4, 1).into();
+
+        assert_eq!(r1.distance(&r2), 5.0);
+        assert_eq!(r2.distance(&r1), 5.0);
+        assert_eq!(r2.distance(&r3), 1.0);
+    }
+
+    #[test]
+    fn distance_circle_circle() {
+        let c1: Shape = Circle::new((0, 0).into(), 2).into();
+        let c2: Shape = Circle::new((6, 8).into(), 3).into();
+        let c3: Shape = Circle::new((1, 1).into(), 3).into();
+
+        assert_eq!(c1.distance(&c2), 5.0);
+        assert_eq!(c1.distance(&c3), 0.0);
+    }
+
+    #[test]
+    fn distance_rect_circle() {
+        let r: Shape = Rectangle::new((0, 0).into(), 4, 4).into();
+        let c1: Shape = Circle::new((10, 2).into(), 2).into();
+        let c2: Shape = Circle::new((2, 2).into(), 1).into();
+
+        assert_eq!(r.distance(&c1), 4.0);
+        assert_eq!(c1.distance(&r), 4.0);
+        assert_eq!(r.distance(&c2), 0.0);
+    }
+
+    #[test]
+    fn mirror_about_axis() {
+        let mut rect: Shape = Rectangle::new((2, 1).into(), 3, 4).into();
+        rect.mirror(Flip::Horizontal, 0);
+        assert_eq!(rect, Rectangle::new((-5, 1).into(), 3, 4).into());
+        rect.mirror(Flip::Vertical, 5);
+        assert_eq!(rect, Rectangle::new((-5, 0).into(), 3, 4).into());
+
+        let mut circle: Shape = Circle::new((4, 5).into(), 2).into();
+        circle.mirror(Flip::Vertical, 2 * 7);
+        assert_eq!(circle, Circle::new((4, 9).into(), 2).into());
+        circle.mirror(Flip::Horizontal, 8);
+        assert_eq!(circle, Circle::new((4, 9).into(), 2).into());
+    }
 }
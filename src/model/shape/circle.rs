@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 use crate::model::vec2::Vec2;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Circle {
     pos: Vec2,
     radius: u32,
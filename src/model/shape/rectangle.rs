@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 use crate::model::vec2::Vec2;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Rectangle {
     pos: Vec2,
     width: u32,
@@ -1,6 +1,8 @@
 use std::ops::{Add, Neg, Sub, AddAssign, SubAssign};
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Vec2 {
     pub x: i32,
     pub y: i32,
@@ -12,8 +14,13 @@ impl Vec2 {
     }
 
     pub fn distance(&self, rhs: &Self) -> u32 {
+        self.exact_distance(rhs) as u32
+    }
+
+    /// Euclidean distance without rounding
+    pub fn exact_distance(&self, rhs: &Self) -> f32 {
         let diff = *self - *rhs;
-        f32::sqrt((diff.x * diff.x + diff.y * diff.y) as f32) as u32
+        f32::sqrt((diff.x * diff.x + diff.y * diff.y) as f32)
     }
 }
 
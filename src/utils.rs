@@ -1 +1,2 @@
 pub mod message_resolver;
+pub mod rng;
@@ -1,8 +1,16 @@
-use iced::widget::{button, column, container, row};
+use iced::widget::{button, checkbox, column, container, row, scrollable, text, text_input};
 use iced::Length;
 
+use crate::controller::state::{
+    AccessDraft, AreaAction, ArrayDraft, BlockDraft, ControllerState, MeasureKind, OptimizerDraft,
+    ShapeType,
+};
 use crate::controller::Controller;
 use crate::message::Message;
+use crate::model::block::Block;
+use crate::model::coverage;
+use crate::model::report::Report;
+use crate::model::shape::{Flip, Shape};
 use crate::utils::message_resolver::*;
 
 use self::plan::plan_view;
@@ -12,23 +20,74 @@ mod plan;
 const SPACING: u16 = 5;
 const PADDING: u16 = 10;
 
-const BUTTONS: [(&str, MessageResolver); 7] = [
+const BUTTONS: [(&str, MessageResolver); 38] = [
+    ("Open", message_if!(Message::OpenButton, Controller::idle)),
+    ("Save", message_if!(Message::SaveButton, Controller::idle)),
     ("Add rectangle", message_if!(Message::AddRectangleButton, Controller::idle)),
     ("Add circle", message_if!(Message::AddCircleButton, Controller::idle)),
     ("Move", message_if!(Message::MoveButton, Controller::idle)),
+    ("Measure distance", message_if!(Message::MeasureButton(MeasureKind::Distance), Controller::idle)),
+    ("Measure path", message_if!(Message::MeasureButton(MeasureKind::Polyline), Controller::idle)),
+    ("Measure area", message_if!(Message::MeasureButton(MeasureKind::Area), Controller::idle)),
+    ("Shape distance", message_if!(Message::MeasureButton(MeasureKind::ShapeDistance), Controller::idle)),
+    ("Dimension", message_if!(Message::DimensionButton, Controller::idle)),
+    ("Set room", message_if!(Message::SetRoomButton, Controller::idle)),
+    ("Optimize layout", message_if!(Message::OptimizeButton, Controller::idle)),
+    ("Accessibility", message_if!(Message::AccessButton, Controller::idle)),
+    ("Egress", message_if!(Message::EgressButton, Controller::idle)),
+    ("Coverage", message_if!(Message::CoverageButton, Controller::idle)),
+    ("Report", message_if!(Message::ReportButton, Controller::idle)),
+    ("Find path", message_if!(Message::MotionButton(false), Controller::has_single_selection)),
+    ("Find path turning", message_if!(Message::MotionButton(true), Controller::has_single_selection)),
+    ("Create block", message_if!(Message::CreateBlockButton, Controller::idle)),
+    ("Group", message_if!(Message::GroupButton, Controller::idle)),
+    ("Ungroup", message_if!(Message::UngroupButton, Controller::idle)),
+    ("Select", message_if!(Message::SelectButton, Controller::idle)),
+    ("Copy", message_if!(Message::CopyButton, Controller::has_selection)),
+    ("Cut", message_if!(Message::CutButton, Controller::has_selection)),
+    ("Paste", message_if!(Message::PasteButton, Controller::idle)),
+    ("Duplicate", message_if!(Message::DuplicateButton, Controller::has_selection)),
+    ("Delete", message_if!(Message::DeleteButton, Controller::has_selection)),
+    ("Array", message_if!(Message::ArrayButton, Controller::has_selection)),
+    ("Flip horizontally", message_if!(Message::FlipButton(Flip::Horizontal), Controller::has_selection)),
+    ("Flip vertically", message_if!(Message::FlipButton(Flip::Vertical), Controller::has_selection)),
+    ("Mirror horizontally", message_if!(Message::MirrorButton(Flip::Horizontal), Controller::has_selection)),
+    ("Mirror vertically", message_if!(Message::MirrorButton(Flip::Vertical), Controller::has_selection)),
     ("Scale up", message_if!(Message::ScaleUp, Controller::idle)),
     ("Scale down", message_if!(Message::ScaleDown, Controller::idle)),
     ("Undo", message_if!(Message::Undo, Controller::can_undo)),
     ("Redo", message_if!(Message::Redo, Controller::can_redo)),
+    ("Previous branch", message_if!(Message::PreviousBranch, Controller::can_switch_branch)),
+    ("Next branch", message_if!(Message::NextBranch, Controller::can_switch_branch)),
 ];
 
 type Element<'a> = iced::Element<'a, Message, iced::Renderer<iced::Theme>>;
 
-pub fn main_view(controller: &Controller) -> Element {
+pub fn main_view(controller: &Controller) -> Element<'_> {
     let buttons = button_panel(controller);
     let plan_view = plan_view(controller);
+    let status_bar = status_bar(controller);
+    let panels = [
+        Some(catalog_panel(controller)),
+        Some(block_panel(controller)),
+        controller.array_draft().map(|draft| array_panel(controller, draft)),
+        controller.optimizer_draft().map(|draft| optimizer_panel(controller, draft)),
+        matches!(controller.state(), ControllerState::AnalyzingAccess)
+            .then(|| access_panel(controller)),
+        matches!(controller.state(), ControllerState::AnalyzingEgress)
+            .then(|| egress_panel(controller)),
+        matches!(controller.state(), ControllerState::AnalyzingCoverage)
+            .then(|| coverage_panel(controller)),
+        controller.report().map(|report| report_panel(controller, report)),
+        Some(history_panel(controller)),
+    ];
+    let side_panel = column(panels.into_iter().flatten().collect())
+        .spacing(SPACING)
+        .width(Length::Fixed(200.0));
 
-    let content = row![buttons, plan_view].spacing(SPACING).padding(PADDING);
+    let content = row![buttons, column![plan_view, status_bar].spacing(SPACING), side_panel]
+        .spacing(SPACING)
+        .padding(PADDING);
 
     container(content)
         .width(Length::Fill)
@@ -36,16 +95,19 @@ pub fn main_view(controller: &Controller) -> Element {
         .into()
 }
 
-fn button_panel(controller: &Controller) -> Element {
-    let buttons = BUTTONS
-        .iter()
-        .map(|(name, message_resolver)| {
+fn button_panel(controller: &Controller) -> Element<'_> {
+    let file_path = text_input("File path", controller.file_path())
+        .on_input(Message::FilePathChanged)
+        .into();
+
+    let buttons = std::iter::once(file_path)
+        .chain(BUTTONS.iter().map(|(name, message_resolver)| {
             button(*name)
                 .on_press_maybe(message_resolver(controller).clone())
                 .style(iced::theme::Button::Positive)
                 .width(Length::Fill)
                 .into()
-        })
+        }))
         .collect();
 
     container(column(buttons).width(Length::Fixed(150.0)).spacing(SPACING))
@@ -54,3 +116,566 @@ fn button_panel(controller: &Controller) -> Element {
         .height(Length::Fill)
         .into()
 }
+
+/// List the items of the catalog, picking one starts placing it. Each item
+/// has the quantity to pack in the room
+fn catalog_panel(controller: &Controller) -> Element<'_> {
+    let entries: Vec<Element<'_>> = controller
+        .catalog()
+        .entries()
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            row![
+                button(text(&entry.name))
+                    .on_press_maybe(controller.idle().then_some(Message::CatalogButton(index)))
+                    .width(Length::Fill),
+                text_input("Qty", controller.packing_draft().quantity(index))
+                    .on_input(move |value| Message::PackQuantityChanged(index, value))
+                    .width(Length::Fixed(50.0)),
+            ]
+            .spacing(SPACING)
+            .into()
+        })
+        .collect();
+
+    let content = if entries.is_empty() {
+        column![text("Catalog"), text("No catalog loaded")]
+    } else {
+        let can_pack = controller.idle() && controller.plan().room().is_some();
+        let pack = button("Pack into room").on_press_maybe(can_pack.then_some(Message::PackButton));
+        column![text("Catalog"), column(entries).spacing(SPACING), pack]
+    };
+
+    container(content.spacing(SPACING))
+        .style(iced::theme::Container::Box)
+        .padding(PADDING)
+        .width(Length::Fill)
+        .into()
+}
+
+/// List the blocks, picking one starts placing instances of it. The block
+/// being edited shows the dimensions of its shapes
+fn block_panel(controller: &Controller) -> Element<'_> {
+    let draft = controller.block_draft();
+
+    let blocks: Vec<Element<'_>> = controller
+        .plan()
+        .blocks()
+        .map(|(block_id, block)| {
+            let header = row![
+                button(text(block.name()))
+                    .on_press_maybe(controller.idle().then_some(Message::BlockButton(*block_id)))
+                    .width(Length::Fill),
+                button("Edit")
+                    .on_press_maybe(
+                        controller.idle().then_some(Message::EditBlockButton(*block_id))
+                    )
+                    .style(iced::theme::Button::Secondary),
+            ]
+            .spacing(SPACING);
+
+            match draft.filter(|draft| draft.block_id() == *block_id) {
+                Some(draft) => column![header, block_editor(block, draft, controller.idle())]
+                    .spacing(SPACING)
+                    .into(),
+                None => header.into(),
+            }
+        })
+        .collect();
+
+    let content = if blocks.is_empty() {
+        column![text("Blocks"), text("No block defined")]
+    } else {
+        column![text("Blocks"), column(blocks).spacing(SPACING)]
+    };
+
+    container(content.spacing(SPACING))
+        .style(iced::theme::Container::Box)
+        .padding(PADDING)
+        .width(Length::Fill)
+        .into()
+}
+
+/// One row of text inputs per shape of the block, with the width and
+/// height of the rectangles and the radius of the circles
+fn block_editor<'a>(block: &'a Block, draft: &'a BlockDraft, idle: bool) -> Element<'a> {
+    let shapes = block
+        .shapes()
+        .iter()
+        .zip(draft.fields())
+        .enumerate()
+        .map(|(shape_index, (shape, fields))| {
+            let placeholders: &[&str] = match shape {
+                Shape::Circle(_) => &["Radius"],
+                Shape::Rectangle(_) => &["Width", "Height"],
+            };
+
+            let inputs = fields
+                .iter()
+                .zip(placeholders)
+                .enumerate()
+                .map(|(field_index, (value, placeholder))| {
+                    text_input(placeholder, value)
+                        .on_input(move |value| {
+                            Message::BlockDraftChanged(shape_index, field_index, value)
+                        })
+                        .on_submit(Message::ApplyBlockEdit)
+                        .into()
+                })
+                .collect();
+
+            row![text(shape.name()).width(Length::Fixed(60.0)), row(inputs).spacing(SPACING)]
+                .spacing(SPACING)
+                .into()
+        })
+        .collect();
+
+    let actions = row![
+        button("Apply").on_press_maybe(idle.then_some(Message::ApplyBlockEdit)),
+        button("Cancel")
+            .on_press(Message::CancelBlockEdit)
+            .style(iced::theme::Button::Secondary),
+    ]
+    .spacing(SPACING);
+
+    column![column(shapes).spacing(SPACING), actions]
+        .spacing(SPACING)
+        .into()
+}
+
+/// Parameters of the array of the selection being prepared, with the number
+/// of copies which fit in the plan
+fn array_panel<'a>(controller: &Controller, draft: &'a ArrayDraft) -> Element<'a> {
+    let inputs = ArrayDraft::FIELD_NAMES
+        .iter()
+        .zip(draft.fields())
+        .enumerate()
+        .map(|(index, (name, value))| {
+            row![
+                text(name).width(Length::Fixed(60.0)),
+                text_input(name, value)
+                    .on_input(move |value| Message::ArrayDraftChanged(index, value))
+                    .on_submit(Message::ApplyArray),
+            ]
+            .spacing(SPACING)
+            .into()
+        })
+        .collect();
+
+    let preview = controller.array_preview();
+    let summary = if draft.layout().is_none() {
+        "Invalid parameters".to_string()
+    } else {
+        let skipped = preview.iter().filter(|(_, fits)| !fits).count();
+        format!("{} copies, {skipped} overlapping or outside", preview.len())
+    };
+
+    let actions = row![
+        button("Apply").on_press(Message::ApplyArray),
+        button("Cancel")
+            .on_press(Message::CancelArray)
+            .style(iced::theme::Button::Secondary),
+    ]
+    .spacing(SPACING);
+
+    let content = column![
+        text("Array"),
+        column(inputs).spacing(SPACING),
+        text("Negative counts go left and up"),
+        text(summary),
+        actions
+    ];
+
+    container(content.spacing(SPACING))
+        .style(iced::theme::Container::Box)
+        .padding(PADDING)
+        .width(Length::Fill)
+        .into()
+}
+
+/// Parameters of the layout optimizer, with the costs of the previewed layout
+fn optimizer_panel<'a>(controller: &Controller, draft: &'a OptimizerDraft) -> Element<'a> {
+    let inputs = OptimizerDraft::FIELD_NAMES
+        .iter()
+        .zip(draft.fields())
+        .enumerate()
+        .map(|(index, (name, value))| {
+            row![
+                text(name).width(Length::Fixed(60.0)),
+                text_input(name, value)
+                    .on_input(move |value| Message::OptimizerDraftChanged(index, value))
+                    .on_submit(Message::PreviewOptimization),
+            ]
+            .spacing(SPACING)
+            .into()
+        })
+        .collect();
+
+    let preview = controller.optimization_preview();
+    let summary = match preview {
+        Some(preview) => format!(
+            "Cost {:.1} to {:.1}, {} shapes moved",
+            preview.initial_cost,
+            preview.cost,
+            preview.shapes.len()
+        ),
+        None => "Moves the selection, or every shape".to_string(),
+    };
+
+    let actions = row![
+        button("Preview").on_press(Message::PreviewOptimization),
+        button("Apply").on_press_maybe(preview.map(|_| Message::ApplyOptimization)),
+        button("Cancel")
+            .on_press(Message::CancelOptimization)
+            .style(iced::theme::Button::Secondary),
+    ]
+    .spacing(SPACING);
+
+    let content = column![
+        text("Optimize layout"),
+        column(inputs).spacing(SPACING),
+        text("Category keeps the items of a category together, as the crow flies"),
+        text(summary),
+        actions
+    ];
+
+    container(content.spacing(SPACING))
+        .style(iced::theme::Container::Box)
+        .padding(PADDING)
+        .width(Length::Fill)
+        .into()
+}
+
+/// Size of the agent of the accessibility analysis, with the shapes it
+/// can't reach
+fn access_panel(controller: &Controller) -> Element<'_> {
+    let inputs = AccessDraft::FIELD_NAMES
+        .iter()
+        .zip(controller.access_draft().fields())
+        .enumerate()
+        .map(|(index, (name, value))| {
+            row![
+                text(name).width(Length::Fixed(60.0)),
+                text_input(name, value)
+                    .on_input(move |value| Message::AccessDraftChanged(index, value)),
+            ]
+            .spacing(SPACING)
+            .into()
+        })
+        .collect();
+
+    let results: Vec<Element<'_>> = match controller.accessibility() {
+        Some(access) if access.unreachable.is_empty() => {
+            vec![text("Every shape is reachable").into()]
+        }
+        Some(access) => std::iter::once(text("Unreachable:").into())
+            .chain(access.unreachable.iter().map(|shape_id| {
+                let plan = controller.plan();
+                let name = plan
+                    .get_metadata(*shape_id)
+                    .and_then(|metadata| metadata.name.clone())
+                    .or_else(|| plan.get_shape(*shape_id).map(|shape| shape.name().to_string()))
+                    .unwrap_or_default();
+                text(name).into()
+            }))
+            .collect(),
+        None => vec![text("Click on the plan to place the entrance").into()],
+    };
+
+    let content = column![
+        text("Accessibility"),
+        column(inputs).spacing(SPACING),
+        column(results).spacing(SPACING)
+    ];
+
+    container(content.spacing(SPACING))
+        .style(iced::theme::Container::Box)
+        .padding(PADDING)
+        .width(Length::Fill)
+        .into()
+}
+
+/// Longest walking distance allowed to an exit, with the distances found
+fn egress_panel(controller: &Controller) -> Element<'_> {
+    let input = row![
+        text("Max distance").width(Length::Fixed(90.0)),
+        text_input("Max distance", controller.egress_limit())
+            .on_input(Message::EgressLimitChanged),
+    ]
+    .spacing(SPACING);
+
+    let results: Vec<Element<'_>> = match controller.egress() {
+        Some(egress) => {
+            let free = egress.points().count();
+            let too_far = egress.too_far().count();
+            let farthest = match egress.farthest() {
+                Some(distance) => format!("Farthest point: {distance:.1}"),
+                None => "No free space".into(),
+            };
+            vec![
+                text(format!("Exits: {}", controller.plan().exits().len())).into(),
+                text(farthest).into(),
+                text(format!(
+                    "Too far: {:.0}% of the free space",
+                    100.0 * too_far as f32 / free.max(1) as f32
+                ))
+                .into(),
+            ]
+        }
+        None => vec![text("Click on the plan to mark the exits").into()],
+    };
+
+    let content = column![text("Egress"), input, column(results).spacing(SPACING)];
+
+    container(content.spacing(SPACING))
+        .style(iced::theme::Container::Box)
+        .padding(PADDING)
+        .width(Length::Fill)
+        .into()
+}
+
+/// Category of the circles covering the floor, with the coverage they give
+fn coverage_panel(controller: &Controller) -> Element<'_> {
+    // The category analyzed can't be chosen again
+    let categories = coverage::categories(controller.plan())
+        .into_iter()
+        .map(|category| {
+            let chosen = controller.coverage_category() == Some(category.as_str());
+            let button = button(text(&category));
+            if chosen {
+                button.into()
+            } else {
+                button.on_press(Message::CoverageCategory(category)).into()
+            }
+        })
+        .collect();
+
+    let obstacles = checkbox(
+        "Shapes cast shadows",
+        controller.coverage_obstacles(),
+        Message::CoverageObstaclesToggled,
+    );
+
+    let results: Vec<Element<'_>> = match controller.coverage() {
+        Some(coverage) => vec![
+            text(format!("Emitters: {}", coverage.emitters.len())).into(),
+            text(format!("Covered: {:.1}%", 100.0 * coverage.covered())).into(),
+            text(format!("Uncovered areas: {}", coverage.uncovered.len())).into(),
+            text(format!("Covered several times: {:.1}%", 100.0 * coverage.redundant())).into(),
+            text(format!("Average overlap: {:.2}", coverage.mean_overlap())).into(),
+        ],
+        None => vec![text("Give a category to circles to analyze their coverage").into()],
+    };
+
+    let content = column![
+        text("Coverage"),
+        row(categories).spacing(SPACING),
+        obstacles,
+        column(results).spacing(SPACING)
+    ];
+
+    container(content.spacing(SPACING))
+        .style(iced::theme::Container::Box)
+        .padding(PADDING)
+        .width(Length::Fill)
+        .into()
+}
+
+/// Occupancy of the plan, with the free space of its room
+fn report_panel<'a>(controller: &'a Controller, report: &'a Report) -> Element<'a> {
+    let mut lines = vec![format!(
+        "Shapes: {} ({:.1})",
+        report.shape_count, report.shape_area
+    )];
+    match report.free_area {
+        Some(free_area) => lines.push(format!("Free space: {free_area:.1}")),
+        None => lines.push("Set a room to measure the free space".into()),
+    }
+    lines.extend(report.categories.iter().map(|stats| {
+        let category = stats.category.as_deref().unwrap_or("Uncategorized");
+        format!("{category}: {} ({:.1})", stats.count, stats.area)
+    }));
+    if let Some(rect) = &report.largest_rectangle {
+        lines.push(format!("Largest empty rectangle: {} x {}", rect.width(), rect.height()));
+    }
+    if let Some((_, radius)) = report.largest_circle {
+        lines.push(format!("Largest empty circle: radius {radius:.1}"));
+    }
+
+    let content = column![
+        text("Report"),
+        column(lines.into_iter().map(|line| text(line).into()).collect()).spacing(SPACING),
+        button("Export CSV")
+            .on_press_maybe(controller.idle().then_some(Message::ExportReportButton))
+    ];
+
+    container(content.spacing(SPACING))
+        .style(iced::theme::Container::Box)
+        .padding(PADDING)
+        .width(Length::Fill)
+        .into()
+}
+
+/// List the history of commands, clicking on an entry undoes or
+/// redoes the commands up to this entry
+fn history_panel(controller: &Controller) -> Element<'_> {
+    let position = controller.history_position();
+
+    let initial_state = std::iter::once("Initial state".to_string());
+    let entries = initial_state
+        .chain(controller.history().into_iter().map(|entry| {
+            if entry.branch_count > 1 {
+                format!("{} ({}/{})", entry.description, entry.branch + 1, entry.branch_count)
+            } else {
+                entry.description
+            }
+        }))
+        .enumerate()
+        .map(|(index, description)| {
+            let style = match index.cmp(&position) {
+                std::cmp::Ordering::Less => iced::theme::Button::Secondary,
+                std::cmp::Ordering::Equal => iced::theme::Button::Primary,
+                std::cmp::Ordering::Greater => iced::theme::Button::Text,
+            };
+
+            button(text(description))
+                .on_press(Message::JumpToHistory(index))
+                .style(style)
+                .width(Length::Fill)
+                .into()
+        })
+        .collect();
+
+    let content = column![
+        text("History"),
+        scrollable(column(entries).spacing(SPACING)).height(Length::Fill)
+    ]
+    .spacing(SPACING);
+
+    container(content)
+        .style(iced::theme::Container::Box)
+        .padding(PADDING)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
+
+fn status_bar(controller: &Controller) -> Element<'_> {
+    let cursor = match controller.cursor() {
+        Some(pos) => format!("({}, {})", pos.x, pos.y),
+        None => "-".into(),
+    };
+
+    let infos = [
+        format!("Mode: {}", mode_name(controller.state())),
+        format!("Cursor: {cursor}"),
+        format!("Scale: {}", controller.scale()),
+        format!("Shapes: {}", controller.plan().iter().count()),
+        controller
+            .notice()
+            .unwrap_or(mode_hint(controller.state()))
+            .into(),
+    ];
+
+    let content = infos.into_iter().map(|info| text(info).into()).collect();
+
+    container(row(content).spacing(4 * SPACING))
+        .style(iced::theme::Container::Box)
+        .padding(PADDING)
+        .width(Length::Fill)
+        .into()
+}
+
+fn mode_name(state: &ControllerState) -> &'static str {
+    match state {
+        ControllerState::Idle => "Idle",
+        ControllerState::AddingShape(ShapeType::Rectangle) => "Adding rectangle",
+        ControllerState::AddingShape(ShapeType::Circle) => "Adding circle",
+        ControllerState::MovingShapes => "Moving shapes",
+        ControllerState::Measuring(MeasureKind::Distance) => "Measuring distance",
+        ControllerState::Measuring(MeasureKind::Polyline) => "Measuring path",
+        ControllerState::Measuring(MeasureKind::Area) => "Measuring area",
+        ControllerState::Measuring(MeasureKind::ShapeDistance) => "Measuring shape distance",
+        ControllerState::Annotating => "Adding dimensions",
+        ControllerState::PlacingItem(_) => "Placing item",
+        ControllerState::SelectingArea(AreaAction::CreateBlock) => "Creating block",
+        ControllerState::SelectingArea(AreaAction::Group) => "Grouping",
+        ControllerState::SelectingArea(AreaAction::Ungroup) => "Ungrouping",
+        ControllerState::SelectingArea(AreaAction::Select) => "Selecting",
+        ControllerState::SelectingArea(AreaAction::SetRoom) => "Setting room",
+        ControllerState::PlacingBlock(_) => "Placing block",
+        ControllerState::Mirroring(Flip::Horizontal) => "Mirroring horizontally",
+        ControllerState::Mirroring(Flip::Vertical) => "Mirroring vertically",
+        ControllerState::PlanningMotion { .. } => "Finding path",
+        ControllerState::AnalyzingAccess => "Accessibility",
+        ControllerState::AnalyzingEgress => "Egress",
+        ControllerState::AnalyzingCoverage => "Coverage",
+    }
+}
+
+fn mode_hint(state: &ControllerState) -> &'static str {
+    match state {
+        ControllerState::Idle => {
+            "Hover a shape to see its dimensions, click to select it. \
+             Ctrl+C, Ctrl+X, Ctrl+V, Ctrl+D and Delete edit the selection"
+        }
+        ControllerState::AddingShape(ShapeType::Rectangle) => {
+            "Click to place a corner, click again to set the size. Right-click to cancel"
+        }
+        ControllerState::AddingShape(ShapeType::Circle) => {
+            "Click to place the center, click again to set the radius. Right-click to cancel"
+        }
+        ControllerState::MovingShapes => "Drag a shape to move it. Right-click to cancel",
+        ControllerState::Measuring(MeasureKind::Distance) => {
+            "Click two points. Right-click to clear, right-click again to cancel"
+        }
+        ControllerState::Measuring(MeasureKind::Polyline | MeasureKind::Area) => {
+            "Click to add points. Right-click to clear, right-click again to cancel"
+        }
+        ControllerState::Measuring(MeasureKind::ShapeDistance) => {
+            "Click two shapes. Right-click to clear, right-click again to cancel"
+        }
+        ControllerState::Annotating => {
+            "Click near a side of a shape to toggle its dimension. Right-click to cancel"
+        }
+        ControllerState::PlacingItem(_) => "Click to place the item. Right-click to stop",
+        ControllerState::SelectingArea(AreaAction::CreateBlock) => {
+            "Drag around the shapes making the block. Right-click to cancel"
+        }
+        ControllerState::SelectingArea(AreaAction::Group) => {
+            "Drag around the shapes to group. Right-click to cancel"
+        }
+        ControllerState::SelectingArea(AreaAction::Ungroup) => {
+            "Drag around the groups to ungroup. Right-click to cancel"
+        }
+        ControllerState::SelectingArea(AreaAction::Select) => {
+            "Drag around the shapes to select. Right-click to cancel"
+        }
+        ControllerState::SelectingArea(AreaAction::SetRoom) => {
+            "Drag from a corner of the room to the opposite one. Right-click to cancel"
+        }
+        ControllerState::PlacingBlock(_) => "Click to place the block. Right-click to stop",
+        ControllerState::Mirroring(Flip::Horizontal) => {
+            "Click to place the vertical axis of the mirror. Right-click to cancel"
+        }
+        ControllerState::Mirroring(Flip::Vertical) => {
+            "Click to place the horizontal axis of the mirror. Right-click to cancel"
+        }
+        ControllerState::PlanningMotion { rotation: false } => {
+            "Click where to move the selected shape to find a path. Right-click to stop"
+        }
+        ControllerState::PlanningMotion { rotation: true } => {
+            "Click where to move the selected shape, turning it if needed. Right-click to stop"
+        }
+        ControllerState::AnalyzingAccess => {
+            "Click to place the entrance, hover to see the path to a spot. Right-click to stop"
+        }
+        ControllerState::AnalyzingEgress => {
+            "Click to add an exit or on an exit to remove it. Right-click to stop"
+        }
+        ControllerState::AnalyzingCoverage => {
+            "Hover to see how many emitters cover a spot. Right-click to stop"
+        }
+    }
+}
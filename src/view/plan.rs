@@ -1,12 +1,13 @@
 
-use iced::widget::canvas::{Frame, Path, Program};
+use iced::widget::canvas::{Event, Frame, Path, Program, Stroke, Text};
 use iced::widget::Canvas;
-use iced::{widget, Color, Length, Point, Size};
+use iced::{alignment, mouse, widget, Color, Length, Point, Size, Vector};
 
 use crate::controller::state::ControllerState;
 use crate::controller::Controller;
 use crate::message::Message;
-use crate::model::plan::ShapeId;
+use crate::model::dimension::DimensionLine;
+use crate::model::plan::{Plan, ShapeId};
 use crate::model::shape::{Circle, Rectangle, Shape};
 use crate::model::vec2::Vec2;
 
@@ -14,6 +15,23 @@ use super::Element;
 
 mod program;
 
+const VALID_PREVIEW_COLOR: Color = Color::from_rgb(0.1, 0.7, 0.2);
+const INVALID_PREVIEW_COLOR: Color = Color::from_rgb(0.85, 0.1, 0.1);
+
+const ROOM_COLOR: Color = Color::from_rgb(0.3, 0.3, 0.6);
+const EXIT_COLOR: Color = Color::from_rgb(0.0, 0.6, 0.3);
+/// Size of the markers of the exits, in pixels
+const EXIT_SIZE: f32 = 10.0;
+
+const DIMENSION_COLOR: Color = Color::from_rgb(0.4, 0.4, 0.4);
+const DIMENSION_TICK_SIZE: f32 = 4.0;
+
+const LABEL_TEXT_SIZE: f32 = 14.0;
+const LABEL_PADDING: f32 = 4.0;
+/// Offset between the cursor and the top left corner of a label,
+/// so the label is not hidden under the cursor
+const LABEL_CURSOR_OFFSET: Vector = Vector::new(16.0, 16.0);
+
 /// Match the different pattern with the condition to select the program
 /// for a canvas and then format it with the given format function
 macro_rules! canvas_select_program_and_format {
@@ -27,12 +45,22 @@ macro_rules! canvas_select_program_and_format {
     };
 }
 
-pub fn plan_view(controller: &Controller) -> Element {
+pub fn plan_view(controller: &Controller) -> Element<'_> {
     canvas_select_program_and_format! {
         controller.state() => {
             ControllerState::Idle => program::Idle::new(controller),
             ControllerState::AddingShape(shape_type) => program::Add::new(controller, *shape_type),
-            ControllerState::MovingShapes => program::Move::new(controller)
+            ControllerState::MovingShapes => program::Move::new(controller),
+            ControllerState::Measuring(kind) => program::Measure::new(controller, *kind),
+            ControllerState::Annotating => program::Annotate::new(controller),
+            ControllerState::PlacingItem(entry) => program::Place::new(controller, entry),
+            ControllerState::SelectingArea(action) => program::SelectArea::new(controller, *action),
+            ControllerState::PlacingBlock(block_id) => program::PlaceBlock::new(controller, *block_id),
+            ControllerState::Mirroring(flip) => program::Mirror::new(controller, *flip),
+            ControllerState::PlanningMotion { .. } => program::Motion::new(controller),
+            ControllerState::AnalyzingAccess => program::Access::new(controller),
+            ControllerState::AnalyzingEgress => program::Egress::new(controller),
+            ControllerState::AnalyzingCoverage => program::Coverage::new(controller)
         }
         format_canvas
     }
@@ -44,6 +72,21 @@ fn format_canvas<P: Program<Message>>(canvas: Canvas<P, Message>) -> Canvas<P, M
     canvas.width(Length::Fill).height(Length::Fill)
 }
 
+/// Build the message publishing the world position of the cursor to the
+/// controller. Only cursor events changing that position produce a message
+fn track_cursor(controller: &Controller, event: &Event, bounds: iced::Rectangle) -> Option<Message> {
+    let cursor = match event {
+        Event::Mouse(mouse::Event::CursorMoved { position }) if bounds.contains(*position) => {
+            let relative_pos = Point::ORIGIN + (*position - bounds.position());
+            Some(screen_to_world(relative_pos, controller.scale()))
+        }
+        Event::Mouse(mouse::Event::CursorMoved { .. } | mouse::Event::CursorLeft) => None,
+        _ => return None,
+    };
+
+    Some(Message::CursorMoved(cursor)).filter(|_| cursor != controller.cursor())
+}
+
 fn draw_shapes<'a>(
     frame: &mut Frame,
     shapes: impl Iterator<Item = &'a (ShapeId, Shape)>,
@@ -55,6 +98,149 @@ fn draw_shapes<'a>(
     }
 }
 
+/// Draw the walls of the room of the `plan`, if it has one, and its exits
+fn draw_room(frame: &mut Frame, plan: &Plan, scale: f32) {
+    if let Some(room) = plan.room() {
+        let top_left = world_to_screen(room.top_left(), scale);
+        let bottom_right = world_to_screen(room.bottom_right(), scale);
+        let size = Size::new(bottom_right.x - top_left.x, bottom_right.y - top_left.y);
+        let path = Path::rectangle(top_left, size);
+        frame.stroke(&path, Stroke::default().with_color(ROOM_COLOR).with_width(3.0));
+    }
+
+    for exit in plan.exits() {
+        let top_left =
+            world_to_screen(*exit, scale) - Vector::new(EXIT_SIZE / 2.0, EXIT_SIZE / 2.0);
+        frame.fill(&Path::rectangle(top_left, Size::new(EXIT_SIZE, EXIT_SIZE)), EXIT_COLOR);
+    }
+}
+
+/// Draw the dimensions of the `plan`. The dimensions attached to a shape
+/// of `preview` are drawn on its previewed state instead, so they follow
+/// the shapes being edited
+fn draw_dimensions(
+    frame: &mut Frame,
+    plan: &Plan,
+    scale: f32,
+    preview: &[(ShapeId, Shape)],
+) {
+    for (_id, dimension, shape) in plan.dimensions() {
+        let shape = preview
+            .iter()
+            .find(|(preview_id, _)| *preview_id == dimension.shape_id())
+            .map_or(shape, |(_, preview_shape)| preview_shape);
+
+        if let Some(line) = dimension.line(shape) {
+            draw_dimension_line(frame, &line, scale);
+        }
+    }
+}
+
+fn draw_dimension_line(frame: &mut Frame, line: &DimensionLine, scale: f32) {
+    let start = world_to_screen(line.start, scale);
+    let end = world_to_screen(line.end, scale);
+
+    // Ticks are perpendicular to the dimension line
+    let direction = end - start;
+    let length = f32::hypot(direction.x, direction.y).max(f32::EPSILON);
+    let tick = Vector::new(-direction.y, direction.x) * (DIMENSION_TICK_SIZE / length);
+
+    let path = Path::new(|builder| {
+        builder.move_to(start);
+        builder.line_to(end);
+        for point in [start, end] {
+            builder.move_to(point - tick);
+            builder.line_to(point + tick);
+        }
+    });
+    frame.stroke(&path, Stroke::default().with_color(DIMENSION_COLOR));
+
+    frame.fill_text(Text {
+        content: line.value.to_string(),
+        position: start + direction * 0.5,
+        color: DIMENSION_COLOR,
+        size: LABEL_TEXT_SIZE,
+        horizontal_alignment: alignment::Horizontal::Center,
+        vertical_alignment: alignment::Vertical::Bottom,
+        ..Default::default()
+    });
+}
+
+/// Draw a ghost of `shape` in green if it can be placed in the `plan`
+/// or in red if it can't. The shapes it collides with are highlighted.
+fn draw_preview(
+    frame: &mut Frame,
+    plan: &Plan,
+    shape: &Shape,
+    ignore: &[ShapeId],
+    scale: f32,
+) {
+    let conflicts = plan.conflicts(shape, ignore);
+
+    for (_id, conflicting) in plan.iter().filter(|(id, _)| conflicts.contains(id)) {
+        let path = draw_shape(conflicting, scale);
+        frame.stroke(
+            &path,
+            Stroke::default()
+                .with_color(INVALID_PREVIEW_COLOR)
+                .with_width(3.0),
+        );
+    }
+
+    draw_preview_shape(frame, shape, conflicts.is_empty(), scale);
+}
+
+/// Draw a shape which is not in the plan yet, colored by whether it is `valid`
+fn draw_preview_shape(frame: &mut Frame, shape: &Shape, valid: bool, scale: f32) {
+    let color = if valid {
+        VALID_PREVIEW_COLOR
+    } else {
+        INVALID_PREVIEW_COLOR
+    };
+
+    let path = draw_shape(shape, scale);
+    frame.fill(&path, Color { a: 0.3, ..color });
+    frame.stroke(&path, Stroke::default().with_color(color).with_width(2.0));
+}
+
+/// Draw a small tooltip box with one line of text per element of `lines`
+/// next to the cursor at `cursor_pos`
+fn draw_label(frame: &mut Frame, cursor_pos: Point, lines: &[String]) {
+    let line_height = LABEL_TEXT_SIZE * 1.2;
+    // Rough estimation of the text width because the canvas can't measure it
+    let max_chars = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let size = Size::new(
+        max_chars as f32 * LABEL_TEXT_SIZE * 0.6 + 2.0 * LABEL_PADDING,
+        lines.len() as f32 * line_height + 2.0 * LABEL_PADDING,
+    );
+    let top_left = cursor_pos + LABEL_CURSOR_OFFSET;
+
+    let background = Path::rectangle(top_left, size);
+    frame.fill(&background, Color::from_rgba(1.0, 1.0, 0.9, 0.9));
+    frame.stroke(&background, Stroke::default().with_color(Color::BLACK));
+
+    for (i, line) in lines.iter().enumerate() {
+        let line_offset = Vector::new(LABEL_PADDING, LABEL_PADDING + i as f32 * line_height);
+        frame.fill_text(Text {
+            content: line.clone(),
+            position: top_left + line_offset,
+            size: LABEL_TEXT_SIZE,
+            ..Default::default()
+        });
+    }
+}
+
+fn format_dimensions(shape: &Shape) -> String {
+    match shape {
+        Shape::Circle(circle) => format!("r = {}", circle.radius()),
+        Shape::Rectangle(rectangle) => format!("{} x {}", rectangle.width(), rectangle.height()),
+    }
+}
+
+fn format_position(pos: Vec2) -> String {
+    format!("({}, {})", pos.x, pos.y)
+}
+
 fn draw_shape(shape: &Shape, scale: f32) -> Path {
     match shape {
         Shape::Circle(circle) => draw_circle(circle, scale),
@@ -6,3 +6,36 @@ pub use add::*;
 
 pub mod r#move;
 pub use r#move::*;
+
+pub mod measure;
+pub use measure::*;
+
+pub mod annotate;
+pub use annotate::*;
+
+pub mod place;
+pub use place::*;
+
+pub mod select_area;
+pub use select_area::*;
+
+pub mod place_block;
+pub use place_block::*;
+
+pub mod mirror;
+pub use mirror::*;
+
+pub mod motion;
+pub use motion::*;
+
+pub mod access;
+pub use access::*;
+
+pub mod egress;
+pub use egress::*;
+
+pub mod coverage;
+pub use coverage::*;
+
+#[cfg(test)]
+pub mod harness;
@@ -0,0 +1,169 @@
+use iced::event::Status;
+use iced::widget::canvas::{Event, Frame, Path, Program, Stroke};
+use iced::{mouse, Color, Size, Vector};
+
+use crate::controller::Controller;
+use crate::message::Message;
+use crate::view;
+use crate::view::plan::{screen_to_world, world_to_screen};
+
+const REACHABLE_COLOR: Color = Color::from_rgba(0.1, 0.7, 0.2, 0.15);
+const BOTTLENECK_COLOR: Color = Color::from_rgba(0.95, 0.55, 0.0, 0.5);
+const UNREACHABLE_COLOR: Color = Color::from_rgb(0.85, 0.1, 0.1);
+const ENTRANCE_COLOR: Color = Color::from_rgb(0.1, 0.3, 0.9);
+const PATH_COLOR: Color = Color::from_rgb(0.1, 0.3, 0.9);
+
+/// Radius of the marker of the entrance, in pixels
+const ENTRANCE_RADIUS: f32 = 6.0;
+
+/// Choose the entrance of the accessibility analysis, and show its results
+/// with the path from the entrance to the cursor
+#[derive(Debug)]
+pub struct Access<'a> {
+    controller: &'a Controller,
+}
+
+impl<'a> Access<'a> {
+    pub fn new(controller: &'a Controller) -> Self {
+        Self { controller }
+    }
+}
+
+impl Program<Message> for Access<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer<iced::Theme>,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> Vec<<iced::Renderer<iced::Theme> as iced::widget::canvas::Renderer>::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let plan = self.controller.plan();
+        let scale = self.controller.scale();
+
+        if let Some(access) = self.controller.accessibility() {
+            // Each point of the grid covers a square around it
+            let size = access.grid().step() as f32 * scale;
+            let cell = |builder: &mut iced::widget::canvas::path::Builder, point| {
+                let top_left = world_to_screen(point, scale) - Vector::new(size / 2.0, size / 2.0);
+                builder.rectangle(top_left, Size::new(size, size));
+            };
+
+            let reachable =
+                Path::new(|builder| access.reachable().for_each(|point| cell(builder, point)));
+            frame.fill(&reachable, REACHABLE_COLOR);
+            let bottlenecks = Path::new(|builder| {
+                access.bottlenecks.iter().flatten().for_each(|point| cell(builder, *point))
+            });
+            frame.fill(&bottlenecks, BOTTLENECK_COLOR);
+        }
+
+        view::plan::draw_room(&mut frame, plan, scale);
+        view::plan::draw_shapes(&mut frame, plan.iter(), scale);
+        view::plan::draw_dimensions(&mut frame, plan, scale, &[]);
+
+        let Some(access) = self.controller.accessibility() else {
+            return vec![frame.into_geometry()];
+        };
+
+        for shape in access.unreachable.iter().filter_map(|id| plan.get_shape(*id)) {
+            let path = view::plan::draw_shape(shape, scale);
+            frame.stroke(&path, Stroke::default().with_color(UNREACHABLE_COLOR).with_width(3.0));
+        }
+
+        if let Some(entrance) = self.controller.entrance() {
+            let marker = Path::circle(world_to_screen(entrance, scale), ENTRANCE_RADIUS);
+            frame.fill(&marker, ENTRANCE_COLOR);
+        }
+
+        if let Some(screen_cursor_pos) = cursor.position_in(bounds) {
+            let world_cursor_pos = screen_to_world(screen_cursor_pos, scale);
+            let label = match access.path_to(world_cursor_pos) {
+                Some((points, length)) => {
+                    let line = Path::new(|builder| {
+                        for (i, point) in points.into_iter().enumerate() {
+                            let point = world_to_screen(point, scale);
+                            if i == 0 {
+                                builder.move_to(point);
+                            } else {
+                                builder.line_to(point);
+                            }
+                        }
+                    });
+                    frame.stroke(&line, Stroke::default().with_color(PATH_COLOR).with_width(2.0));
+                    format!("Walking distance: {length:.1}")
+                }
+                None => "Unreachable".into(),
+            };
+            view::plan::draw_label(&mut frame, screen_cursor_pos, &[label]);
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: Event,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> (Status, Option<Message>) {
+        let cursor_message = view::plan::track_cursor(self.controller, &event, bounds);
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let Some(screen_cursor_pos) = cursor.position_in(bounds) else {
+                    return (Status::Ignored, None);
+                };
+
+                let world_cursor_pos = screen_to_world(screen_cursor_pos, self.controller.scale());
+                (Status::Captured, Some(Message::SetEntrance(world_cursor_pos)))
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                (Status::Captured, Some(Message::Cancel))
+            }
+            _ => (Status::Ignored, cursor_message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::message::Message;
+    use crate::model::shape::Rectangle;
+    use crate::view::plan::program::harness::Harness;
+
+    #[test]
+    fn analysis_follows_entrance_and_agent() {
+        let mut harness = Harness::default();
+        harness.send(Message::SetRoomButton);
+        harness.drag((0, 0), (30, 20));
+        // A desk in a recess 4 units wide
+        harness.send(Message::AddShape(Rectangle::new((20, 0).into(), 2, 10).into()));
+        harness.send(Message::AddShape(Rectangle::new((26, 0).into(), 4, 10).into()));
+        harness.send(Message::AddShape(Rectangle::new((23, 1).into(), 2, 2).into()));
+
+        harness.send(Message::AccessButton);
+        assert!(harness.controller.accessibility().is_none());
+        harness.click((5, 15));
+        let access = harness.controller.accessibility().unwrap();
+        assert_eq!(access.unreachable.len(), 1);
+        assert!(!access.bottlenecks.is_empty());
+
+        harness.send(Message::AccessDraftChanged(0, "3".into()));
+        let access = harness.controller.accessibility().unwrap();
+        assert!(access.unreachable.is_empty());
+
+        // Removing the desk with the history updates the analysis
+        harness.send(Message::AccessDraftChanged(0, "5".into()));
+        harness.send(Message::Undo);
+        let access = harness.controller.accessibility().unwrap();
+        assert!(access.unreachable.is_empty());
+
+        harness.send(Message::Cancel);
+        assert!(harness.controller.accessibility().is_none());
+    }
+}
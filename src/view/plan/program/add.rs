@@ -1,8 +1,8 @@
 use std::mem;
 
 use iced::event::Status;
-use iced::widget::canvas::{Event, Frame, Program, Stroke};
-use iced::{mouse, Color, Point};
+use iced::widget::canvas::{Event, Frame, Path, Program};
+use iced::{mouse, Point};
 
 use crate::controller::state::ShapeType;
 use crate::controller::Controller;
@@ -12,6 +12,8 @@ use crate::model::vec2::Vec2;
 use crate::view;
 use crate::view::plan::screen_to_world;
 
+const CURSOR_MARKER_RADIUS: f32 = 4.0;
+
 #[derive(Debug, Default)]
 pub enum State {
     #[default]
@@ -22,6 +24,14 @@ pub enum State {
     },
 }
 
+impl State {
+    /// The controller cancels the gesture when the history is used while sizing,
+    /// the state is then stale and must be handled as `Positioning`
+    fn is_cancelled(&self, controller: &Controller) -> bool {
+        matches!(self, State::Sizing { .. }) && !controller.gesture_in_progress()
+    }
+}
+
 #[derive(Debug)]
 pub struct Add<'a> {
     controller: &'a Controller,
@@ -46,23 +56,67 @@ impl Program<Message> for Add<'_> {
         renderer: &iced::Renderer<iced::Theme>,
         _theme: &iced::Theme,
         bounds: iced::Rectangle,
-        _cursor: iced::advanced::mouse::Cursor,
+        cursor: iced::advanced::mouse::Cursor,
     ) -> Vec<<iced::Renderer<iced::Theme> as iced::widget::canvas::Renderer>::Geometry> {
         let mut frame = Frame::new(renderer, bounds.size());
 
+        view::plan::draw_room(&mut frame, self.controller.plan(), self.controller.scale());
         view::plan::draw_shapes(
             &mut frame,
             self.controller.plan().iter(),
             self.controller.scale(),
         );
+        view::plan::draw_dimensions(
+            &mut frame,
+            self.controller.plan(),
+            self.controller.scale(),
+            &[],
+        );
+
+        let scale = self.controller.scale();
+        let screen_cursor_pos = cursor.position_in(bounds);
+
+        let state = if state.is_cancelled(self.controller) {
+            &State::Positioning
+        } else {
+            state
+        };
+
+        match state {
+            State::Positioning => {
+                if let Some(screen_cursor_pos) = screen_cursor_pos {
+                    let world_cursor_pos = screen_to_world(screen_cursor_pos, scale);
+                    let shape =
+                        generate_shape(self.shape_type, world_cursor_pos, world_cursor_pos);
+                    let color = if self.controller.plan().is_disjoint(&shape, None) {
+                        view::plan::VALID_PREVIEW_COLOR
+                    } else {
+                        view::plan::INVALID_PREVIEW_COLOR
+                    };
 
-        if let State::Sizing { shape, .. } = state {
-            let path = view::plan::draw_shape(shape, self.controller.scale());
+                    frame.fill(&Path::circle(screen_cursor_pos, CURSOR_MARKER_RADIUS), color);
+                    view::plan::draw_label(
+                        &mut frame,
+                        screen_cursor_pos,
+                        &[view::plan::format_position(world_cursor_pos)],
+                    );
+                }
+            }
+            State::Sizing { shape, .. } => {
+                view::plan::draw_preview(&mut frame, self.controller.plan(), shape, &[], scale);
 
-            frame.stroke(
-                &path,
-                Stroke::default().with_color(Color::BLACK).with_width(2.0),
-            );
+                if let Some(screen_cursor_pos) = screen_cursor_pos {
+                    let world_cursor_pos = screen_to_world(screen_cursor_pos, scale);
+                    view::plan::draw_label(
+                        &mut frame,
+                        screen_cursor_pos,
+                        &[
+                            view::plan::format_dimensions(shape),
+                            view::plan::format_position(world_cursor_pos),
+                        ],
+                    );
+                }
+            }
         }
 
         vec![frame.into_geometry()]
@@ -75,6 +129,12 @@ impl Program<Message> for Add<'_> {
         bounds: iced::Rectangle,
         cursor: iced::advanced::mouse::Cursor,
     ) -> (Status, Option<Message>) {
+        let cursor_message = view::plan::track_cursor(self.controller, &event, bounds);
+
+        if state.is_cancelled(self.controller) {
+            *state = State::Positioning;
+        }
+
         match (&mut *state, event) {
             (
                 State::Positioning,
@@ -91,6 +151,8 @@ impl Program<Message> for Add<'_> {
                         shape,
                         first_point: world_cursor_pos,
                     };
+
+                    return (Status::Captured, Some(Message::GestureStarted));
                 }
 
                 (Status::Captured, None)
@@ -108,23 +170,25 @@ impl Program<Message> for Add<'_> {
                     let relative_pos = Point::ORIGIN + (absolute_pos - bounds.position());
                     let second_point = screen_to_world(relative_pos, self.controller.scale());
 
-                    let new_shape = generate_shape(self.shape_type, *first_point, second_point);
-                    if self.controller.plan().is_disjoint(&new_shape, None) {
-                        *shape = new_shape
-                    }
+                    *shape = generate_shape(self.shape_type, *first_point, second_point);
 
-                    (Status::Captured, None)
+                    (Status::Captured, cursor_message)
                 } else {
-                    (Status::Ignored, None)
+                    (Status::Ignored, cursor_message)
                 }
             }
             (
-                State::Sizing { .. },
+                State::Sizing { shape, .. },
                 Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)),
             ) => {
                 if cursor.position_in(bounds).is_none() {
                     return (Status::Ignored, None);
                 }
+                // The preview follows the cursor even on invalid positions,
+                // the shape is only added once it doesn't collide anymore
+                if !self.controller.plan().is_disjoint(shape, None) {
+                    return (Status::Captured, None);
+                }
                 // We replace the state with Positioning to move shape and not clone it
                 // The new state does't matter because the message sent should make the
                 // controller switch back to the Idle program
@@ -133,11 +197,10 @@ impl Program<Message> for Add<'_> {
 
                 (Status::Captured, Some(Message::AddShape(shape)))
             }
-            (
-                State::Sizing { .. },
-                Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)),
-            ) => (Status::Captured, Some(Message::Cancel)),
-            _ => (Status::Ignored, None),
+            (_, Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right))) => {
+                (Status::Captured, Some(Message::Cancel))
+            }
+            _ => (Status::Ignored, cursor_message),
         }
     }
 }
@@ -167,3 +230,81 @@ fn generate_recangle(first_point: Vec2, second_point: Vec2) -> Shape {
         height,
     ))
 }
+
+#[cfg(test)]
+mod test {
+    use iced::mouse;
+
+    use crate::message::Message;
+    use crate::model::shape::{Circle, Rectangle, Shape};
+    use crate::view::plan::program::harness::Harness;
+
+    #[test]
+    fn add_rectangle() {
+        let mut harness = Harness::default();
+        harness.send(Message::AddRectangleButton);
+
+        harness.click((6, 5));
+        harness.click((2, 2));
+
+        let shapes: Vec<_> = harness
+            .controller
+            .plan()
+            .iter()
+            .map(|(_, s)| s.clone())
+            .collect();
+        assert_eq!(shapes, [Shape::from(Rectangle::new((2, 2).into(), 4, 3))]);
+        assert!(harness.controller.idle());
+    }
+
+    #[test]
+    fn add_circle() {
+        let mut harness = Harness::default();
+        harness.send(Message::AddCircleButton);
+
+        harness.click((10, 10));
+        harness.click((13, 14));
+
+        let shapes: Vec<_> = harness
+            .controller
+            .plan()
+            .iter()
+            .map(|(_, s)| s.clone())
+            .collect();
+        assert_eq!(shapes, [Shape::from(Circle::new((10, 10).into(), 5))]);
+    }
+
+    #[test]
+    fn overlapping_shape_is_not_added() {
+        let mut harness = Harness::default();
+        harness.send(Message::AddShape(
+            Rectangle::new((5, 5).into(), 2, 2).into(),
+        ));
+        harness.send(Message::AddRectangleButton);
+
+        harness.click((0, 0));
+        harness.click((10, 10));
+
+        assert_eq!(harness.controller.plan().iter().count(), 1);
+        assert!(!harness.controller.idle());
+
+        // The preview follows the cursor back to a valid position
+        harness.click((4, 4));
+        assert_eq!(harness.controller.plan().iter().count(), 2);
+    }
+
+    #[test]
+    fn undo_while_sizing_cancels_the_shape() {
+        let mut harness = Harness::default();
+        harness.send(Message::AddRectangleButton);
+
+        harness.click((0, 0));
+        harness.send(Message::Undo);
+        harness.move_to((3, 3));
+        harness.press(mouse::Button::Left);
+
+        // The first click after the undo starts a new shape
+        assert_eq!(harness.controller.plan().iter().count(), 0);
+        assert!(harness.controller.gesture_in_progress());
+    }
+}
@@ -0,0 +1,135 @@
+use iced::event::Status;
+use iced::widget::canvas::{Event, Frame, Program, Stroke};
+use iced::mouse;
+
+use crate::controller::Controller;
+use crate::message::Message;
+use crate::model::dimension::{Dimension, DimensionKind};
+use crate::model::plan::ShapeId;
+use crate::model::shape::Shape;
+use crate::model::vec2::Vec2;
+use crate::view;
+use crate::view::plan::screen_to_world;
+
+#[derive(Debug)]
+pub struct Annotate<'a> {
+    controller: &'a Controller,
+}
+
+impl<'a> Annotate<'a> {
+    pub fn new(controller: &'a Controller) -> Self {
+        Self { controller }
+    }
+
+    /// Add the dimension of the shape under `point` matching the nearest
+    /// side, or remove it if the shape already has it
+    fn toggle_dimension(&self, shape_id: ShapeId, shape: &Shape, point: Vec2) -> Message {
+        let kind = dimension_kind_at(shape, point);
+
+        let existing = self
+            .controller
+            .plan()
+            .dimensions()
+            .find(|(_, dimension, _)| {
+                dimension.shape_id() == shape_id && dimension.kind() == kind
+            });
+
+        match existing {
+            Some((dimension_id, _, _)) => Message::RemoveDimension(dimension_id),
+            None => Message::AddDimension(Dimension::new(shape_id, kind)),
+        }
+    }
+}
+
+impl Program<Message> for Annotate<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer<iced::Theme>,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> Vec<<iced::Renderer<iced::Theme> as iced::widget::canvas::Renderer>::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let plan = self.controller.plan();
+        let scale = self.controller.scale();
+
+        view::plan::draw_room(&mut frame, plan, scale);
+        view::plan::draw_shapes(&mut frame, plan.iter(), scale);
+        view::plan::draw_dimensions(&mut frame, plan, scale, &[]);
+
+        if let Some(screen_cursor_pos) = cursor.position_in(bounds) {
+            let world_cursor_pos = screen_to_world(screen_cursor_pos, scale);
+            if let Some((_, shape)) = plan.get_shape_at(&world_cursor_pos) {
+                let path = view::plan::draw_shape(shape, scale);
+                frame.stroke(
+                    &path,
+                    Stroke::default()
+                        .with_color(view::plan::DIMENSION_COLOR)
+                        .with_width(2.0),
+                );
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: Event,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> (Status, Option<Message>) {
+        let cursor_message = view::plan::track_cursor(self.controller, &event, bounds);
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let Some(screen_cursor_pos) = cursor.position_in(bounds) else {
+                    return (Status::Ignored, None);
+                };
+
+                let world_cursor_pos = screen_to_world(screen_cursor_pos, self.controller.scale());
+                let message = self
+                    .controller
+                    .plan()
+                    .get_shape_at(&world_cursor_pos)
+                    .map(|(shape_id, shape)| {
+                        self.toggle_dimension(*shape_id, shape, world_cursor_pos)
+                    });
+
+                (Status::Captured, message)
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                (Status::Captured, Some(Message::Cancel))
+            }
+            _ => (Status::Ignored, cursor_message),
+        }
+    }
+}
+
+/// Rectangles get their width when clicked near the top or bottom side
+/// and their height when clicked near the left or right side
+fn dimension_kind_at(shape: &Shape, point: Vec2) -> DimensionKind {
+    match shape {
+        Shape::Circle(_) => DimensionKind::Radius,
+        Shape::Rectangle(rect) => {
+            let to_horizontal_side = i32::min(
+                (point.y - rect.pos().y).abs(),
+                (rect.pos().y + rect.height() as i32 - point.y).abs(),
+            );
+            let to_vertical_side = i32::min(
+                (point.x - rect.pos().x).abs(),
+                (rect.pos().x + rect.width() as i32 - point.x).abs(),
+            );
+
+            if to_horizontal_side <= to_vertical_side {
+                DimensionKind::Width
+            } else {
+                DimensionKind::Height
+            }
+        }
+    }
+}
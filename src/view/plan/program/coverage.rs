@@ -0,0 +1,143 @@
+use iced::event::Status;
+use iced::widget::canvas::{Event, Frame, Path, Program};
+use iced::{mouse, Color, Size, Vector};
+
+use crate::controller::Controller;
+use crate::message::Message;
+use crate::view;
+use crate::view::plan::{screen_to_world, world_to_screen};
+
+const UNCOVERED_COLOR: Color = Color::from_rgba(0.85, 0.1, 0.1, 0.4);
+const COVERED_COLOR: Color = Color::from_rgba(0.1, 0.7, 0.2, 0.25);
+const REDUNDANT_COLOR: Color = Color::from_rgba(0.1, 0.3, 0.9, 0.3);
+
+/// Show the floor covered by the circles of a category, the areas they
+/// leave uncovered and the ones they cover several times
+#[derive(Debug)]
+pub struct Coverage<'a> {
+    controller: &'a Controller,
+}
+
+impl<'a> Coverage<'a> {
+    pub fn new(controller: &'a Controller) -> Self {
+        Self { controller }
+    }
+}
+
+impl Program<Message> for Coverage<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer<iced::Theme>,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> Vec<<iced::Renderer<iced::Theme> as iced::widget::canvas::Renderer>::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let plan = self.controller.plan();
+        let scale = self.controller.scale();
+        let coverage = self.controller.coverage();
+
+        if let Some(coverage) = coverage {
+            // Each point of the grid covers a square around it
+            let size = coverage.grid().step() as f32 * scale;
+            let colors = [UNCOVERED_COLOR, COVERED_COLOR, REDUNDANT_COLOR];
+            for (band, color) in colors.into_iter().enumerate() {
+                let path = Path::new(|builder| {
+                    let points = coverage.points();
+                    for (point, _) in points.filter(|(_, count)| (*count as usize).min(2) == band) {
+                        let top_left =
+                            world_to_screen(point, scale) - Vector::new(size / 2.0, size / 2.0);
+                        builder.rectangle(top_left, Size::new(size, size));
+                    }
+                });
+                frame.fill(&path, color);
+            }
+        }
+
+        view::plan::draw_room(&mut frame, plan, scale);
+        view::plan::draw_shapes(&mut frame, plan.iter(), scale);
+        view::plan::draw_dimensions(&mut frame, plan, scale, &[]);
+
+        if let (Some(coverage), Some(screen_cursor_pos)) = (coverage, cursor.position_in(bounds)) {
+            let world_cursor_pos = screen_to_world(screen_cursor_pos, scale);
+            if let Some(count) = coverage.count_at(world_cursor_pos) {
+                let label = match count {
+                    0 => "Uncovered".into(),
+                    1 => "Covered by 1 emitter".into(),
+                    count => format!("Covered by {count} emitters"),
+                };
+                view::plan::draw_label(&mut frame, screen_cursor_pos, &[label]);
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: Event,
+        bounds: iced::Rectangle,
+        _cursor: iced::advanced::mouse::Cursor,
+    ) -> (Status, Option<Message>) {
+        let cursor_message = view::plan::track_cursor(self.controller, &event, bounds);
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                (Status::Captured, Some(Message::Cancel))
+            }
+            _ => (Status::Ignored, cursor_message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::message::Message;
+    use crate::model::metadata::Metadata;
+    use crate::model::shape::{Circle, Rectangle};
+    use crate::view::plan::program::harness::Harness;
+
+    fn category(name: &str) -> Metadata {
+        Metadata {
+            name: None,
+            category: Some(name.into()),
+        }
+    }
+
+    #[test]
+    fn coverage_follows_category_and_obstacles() {
+        let mut harness = Harness::default();
+        harness.send(Message::SetRoomButton);
+        harness.drag((0, 0), (20, 10));
+        let lamp = Circle::new((5, 5).into(), 8).into();
+        harness.send(Message::AddItem(lamp, category("lamp")));
+        let access_point = Circle::new((15, 5).into(), 20).into();
+        harness.send(Message::AddItem(access_point, category("wifi")));
+        harness.send(Message::AddShape(Rectangle::new((8, 0).into(), 1, 10).into()));
+
+        // The first category is analyzed by default
+        harness.send(Message::CoverageButton);
+        assert_eq!(harness.controller.coverage_category(), Some("lamp"));
+        let coverage = harness.controller.coverage().unwrap();
+        assert_eq!(coverage.count_at((10, 5).into()), Some(1));
+        assert!(coverage.covered() < 1.0);
+
+        harness.send(Message::CoverageObstaclesToggled(true));
+        let coverage = harness.controller.coverage().unwrap();
+        assert_eq!(coverage.count_at((10, 5).into()), Some(0));
+
+        harness.send(Message::CoverageCategory("wifi".into()));
+        assert!(harness.controller.coverage().unwrap().covered() < 1.0);
+        harness.send(Message::CoverageObstaclesToggled(false));
+        let coverage = harness.controller.coverage().unwrap();
+        assert_eq!(coverage.covered(), 1.0);
+        assert_eq!(coverage.redundant(), 0.0);
+
+        harness.send(Message::Cancel);
+        assert!(harness.controller.coverage().is_none());
+    }
+}
@@ -0,0 +1,189 @@
+use iced::event::Status;
+use iced::widget::canvas::{Event, Frame, Path, Program};
+use iced::{mouse, Color, Size, Vector};
+
+use crate::controller::Controller;
+use crate::message::Message;
+use crate::view;
+use crate::view::plan::{screen_to_world, world_to_screen};
+
+/// Colors of the heatmap for the points at no distance and at the maximum
+/// distance from an exit
+const NEAR_COLOR: Color = Color::from_rgba(0.1, 0.7, 0.2, 0.3);
+const FAR_COLOR: Color = Color::from_rgba(0.95, 0.55, 0.0, 0.3);
+/// Color of the points farther than the maximum distance from every exit
+const TOO_FAR_COLOR: Color = Color::from_rgba(0.85, 0.1, 0.1, 0.55);
+/// Number of colors of the heatmap, between the near and far colors
+const HEATMAP_BANDS: usize = 8;
+
+/// Largest distance from an exit to the cursor picking it, in pixels
+const EXIT_PICK_RADIUS: f32 = 8.0;
+
+/// Mark the exits of the plan, and show the walking distance from every
+/// free point to the nearest exit as a heatmap
+#[derive(Debug)]
+pub struct Egress<'a> {
+    controller: &'a Controller,
+}
+
+impl<'a> Egress<'a> {
+    pub fn new(controller: &'a Controller) -> Self {
+        Self { controller }
+    }
+}
+
+impl Program<Message> for Egress<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer<iced::Theme>,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> Vec<<iced::Renderer<iced::Theme> as iced::widget::canvas::Renderer>::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let plan = self.controller.plan();
+        let scale = self.controller.scale();
+        let egress = self.controller.egress();
+
+        if let Some(egress) = egress {
+            // Each point of the grid covers a square around it, and the
+            // points are filled band by band
+            let size = egress.grid().step() as f32 * scale;
+            let mut bands: Vec<_> = (0..=HEATMAP_BANDS).map(|_| Vec::new()).collect();
+            for (point, distance) in egress.points() {
+                let band = match distance {
+                    Some(distance) if distance <= egress.max_distance() => {
+                        let ratio = distance / egress.max_distance();
+                        ((ratio * HEATMAP_BANDS as f32) as usize).min(HEATMAP_BANDS - 1)
+                    }
+                    _ => HEATMAP_BANDS,
+                };
+                bands[band].push(point);
+            }
+
+            for (band, points) in bands.into_iter().enumerate() {
+                let path = Path::new(|builder| {
+                    for point in points {
+                        let top_left =
+                            world_to_screen(point, scale) - Vector::new(size / 2.0, size / 2.0);
+                        builder.rectangle(top_left, Size::new(size, size));
+                    }
+                });
+                frame.fill(&path, band_color(band));
+            }
+        }
+
+        view::plan::draw_room(&mut frame, plan, scale);
+        view::plan::draw_shapes(&mut frame, plan.iter(), scale);
+        view::plan::draw_dimensions(&mut frame, plan, scale, &[]);
+
+        if let (Some(egress), Some(screen_cursor_pos)) = (egress, cursor.position_in(bounds)) {
+            let world_cursor_pos = screen_to_world(screen_cursor_pos, scale);
+            if let Some(distance) = egress.distance_at(world_cursor_pos) {
+                let mut label = format!("Distance to exit: {distance:.1}");
+                if distance > egress.max_distance() {
+                    label += " (too far)";
+                }
+                view::plan::draw_label(&mut frame, screen_cursor_pos, &[label]);
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: Event,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> (Status, Option<Message>) {
+        let cursor_message = view::plan::track_cursor(self.controller, &event, bounds);
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let Some(screen_cursor_pos) = cursor.position_in(bounds) else {
+                    return (Status::Ignored, None);
+                };
+
+                // Clicking on an exit removes it
+                let scale = self.controller.scale();
+                let picked = self.controller.plan().exits().iter().position(|exit| {
+                    screen_cursor_pos.distance(world_to_screen(*exit, scale)) <= EXIT_PICK_RADIUS
+                });
+                let message = match picked {
+                    Some(index) => Message::RemoveExit(index),
+                    None => Message::AddExit(screen_to_world(screen_cursor_pos, scale)),
+                };
+                (Status::Captured, Some(message))
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                (Status::Captured, Some(Message::Cancel))
+            }
+            _ => (Status::Ignored, cursor_message),
+        }
+    }
+}
+
+/// Color of a band of the heatmap, the last band holding the points too far
+/// from the exits
+fn band_color(band: usize) -> Color {
+    if band >= HEATMAP_BANDS {
+        return TOO_FAR_COLOR;
+    }
+
+    let t = band as f32 / (HEATMAP_BANDS - 1) as f32;
+    let mix = |near: f32, far: f32| near + (far - near) * t;
+    Color::from_rgba(
+        mix(NEAR_COLOR.r, FAR_COLOR.r),
+        mix(NEAR_COLOR.g, FAR_COLOR.g),
+        mix(NEAR_COLOR.b, FAR_COLOR.b),
+        mix(NEAR_COLOR.a, FAR_COLOR.a),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::message::Message;
+    use crate::model::shape::Rectangle;
+    use crate::model::vec2::Vec2;
+    use crate::view::plan::program::harness::Harness;
+
+    #[test]
+    fn exits_are_marked_and_removed_on_the_plan() {
+        let mut harness = Harness::default();
+        harness.send(Message::SetRoomButton);
+        harness.drag((0, 0), (40, 20));
+        harness.send(Message::AddShape(Rectangle::new((18, 0).into(), 4, 16).into()));
+
+        harness.send(Message::EgressButton);
+        assert!(harness.controller.egress().is_none());
+        harness.click((0, 10));
+        assert_eq!(harness.controller.plan().exits(), [Vec2::new(0, 10)]);
+        let egress = harness.controller.egress().unwrap();
+        assert!(egress.too_far().count() == 0);
+        // Around the bottom of the wall
+        assert!(egress.distance_at((30, 2).into()).unwrap() > 35.0);
+
+        harness.send(Message::EgressLimitChanged("25".into()));
+        let egress = harness.controller.egress().unwrap();
+        assert!(egress.too_far().any(|point| point == Vec2::new(30, 2)));
+
+        harness.click((40, 10));
+        assert_eq!(harness.controller.plan().exits().len(), 2);
+        assert_eq!(harness.controller.egress().unwrap().too_far().count(), 0);
+
+        // Clicking on an exit removes it, and the history brings it back
+        harness.click((0, 10));
+        assert_eq!(harness.controller.plan().exits(), [Vec2::new(40, 10)]);
+        harness.send(Message::Undo);
+        assert_eq!(harness.controller.plan().exits().len(), 2);
+
+        harness.send(Message::Cancel);
+        assert!(harness.controller.egress().is_none());
+        assert_eq!(harness.controller.plan().exits().len(), 2);
+    }
+}
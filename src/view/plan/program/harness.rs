@@ -0,0 +1,185 @@
+//! Headless harness driving the canvas programs with synthetic mouse events.
+//!
+//! Like the canvas widget, the harness rebuilds the program matching the state
+//! of the controller for every event while keeping the state of the program
+//! between events, and resets it when the program changes. The messages
+//! emitted by the programs are fed into the controller.
+
+use std::any::Any;
+
+use iced::widget::canvas::{Event, Program};
+use iced::{keyboard, mouse, Point, Rectangle};
+
+use crate::controller::state::ControllerState;
+use crate::controller::Controller;
+use crate::message::Message;
+use crate::model::vec2::Vec2;
+use crate::view::plan::{program, world_to_screen};
+
+pub struct Harness {
+    pub controller: Controller,
+    /// Every message emitted by the programs, in order
+    pub messages: Vec<Message>,
+    state: Option<Box<dyn Any>>,
+    bounds: Rectangle,
+    cursor: Point,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self {
+            controller: Controller::default(),
+            messages: Vec::new(),
+            state: None,
+            // The canvas is not at the origin of the window to check that
+            // the programs work with positions relative to their bounds
+            bounds: Rectangle::new(Point::new(150.0, 30.0), iced::Size::new(800.0, 600.0)),
+            cursor: Point::ORIGIN,
+        }
+    }
+}
+
+impl Harness {
+    /// Send a message directly to the controller, like a button would
+    pub fn send(&mut self, message: Message) {
+        self.controller.update(message);
+    }
+
+    /// Move the cursor over the world position `pos`
+    pub fn move_to(&mut self, pos: impl Into<Vec2>) {
+        let screen_pos = world_to_screen(pos.into(), self.controller.scale());
+        self.cursor = screen_pos + (self.bounds.position() - Point::ORIGIN);
+        self.dispatch(Event::Mouse(mouse::Event::CursorMoved {
+            position: self.cursor,
+        }));
+    }
+
+    pub fn press(&mut self, button: mouse::Button) {
+        self.dispatch(Event::Mouse(mouse::Event::ButtonPressed(button)));
+    }
+
+    pub fn release(&mut self, button: mouse::Button) {
+        self.dispatch(Event::Mouse(mouse::Event::ButtonReleased(button)));
+    }
+
+    pub fn press_key(&mut self, key_code: keyboard::KeyCode, modifiers: keyboard::Modifiers) {
+        self.dispatch(Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code,
+            modifiers,
+        }));
+    }
+
+    /// Left click on the world position `pos`
+    pub fn click(&mut self, pos: impl Into<Vec2>) {
+        self.move_to(pos);
+        self.press(mouse::Button::Left);
+        self.release(mouse::Button::Left);
+    }
+
+    /// Drag with the left button from the world position `from` to `to`
+    pub fn drag(&mut self, from: impl Into<Vec2>, to: impl Into<Vec2>) {
+        self.move_to(from);
+        self.press(mouse::Button::Left);
+        self.move_to(to);
+        self.release(mouse::Button::Left);
+    }
+
+    fn dispatch(&mut self, event: Event) {
+        let controller = &self.controller;
+        let state = &mut self.state;
+        let bounds = self.bounds;
+        let cursor = mouse::Cursor::Available(self.cursor);
+
+        let message = match controller.state() {
+            ControllerState::Idle => {
+                update(program::Idle::new(controller), state, event, bounds, cursor)
+            }
+            ControllerState::AddingShape(shape_type) => update(
+                program::Add::new(controller, *shape_type),
+                state,
+                event,
+                bounds,
+                cursor,
+            ),
+            ControllerState::MovingShapes => {
+                update(program::Move::new(controller), state, event, bounds, cursor)
+            }
+            ControllerState::Measuring(kind) => update(
+                program::Measure::new(controller, *kind),
+                state,
+                event,
+                bounds,
+                cursor,
+            ),
+            ControllerState::Annotating => update(
+                program::Annotate::new(controller),
+                state,
+                event,
+                bounds,
+                cursor,
+            ),
+            ControllerState::PlacingItem(entry) => update(
+                program::Place::new(controller, entry),
+                state,
+                event,
+                bounds,
+                cursor,
+            ),
+            ControllerState::SelectingArea(action) => update(
+                program::SelectArea::new(controller, *action),
+                state,
+                event,
+                bounds,
+                cursor,
+            ),
+            ControllerState::PlacingBlock(block_id) => update(
+                program::PlaceBlock::new(controller, *block_id),
+                state,
+                event,
+                bounds,
+                cursor,
+            ),
+            ControllerState::Mirroring(flip) => update(
+                program::Mirror::new(controller, *flip),
+                state,
+                event,
+                bounds,
+                cursor,
+            ),
+            ControllerState::PlanningMotion { .. } => {
+                update(program::Motion::new(controller), state, event, bounds, cursor)
+            }
+            ControllerState::AnalyzingAccess => {
+                update(program::Access::new(controller), state, event, bounds, cursor)
+            }
+            ControllerState::AnalyzingEgress => {
+                update(program::Egress::new(controller), state, event, bounds, cursor)
+            }
+            ControllerState::AnalyzingCoverage => {
+                update(program::Coverage::new(controller), state, event, bounds, cursor)
+            }
+        };
+
+        if let Some(message) = message {
+            self.messages.push(message.clone());
+            self.controller.update(message);
+        }
+    }
+}
+
+/// Update the `program` with its state stored in `state`, which is reset
+/// if it belongs to another type of program
+fn update<P: Program<Message>>(
+    program: P,
+    state: &mut Option<Box<dyn Any>>,
+    event: Event,
+    bounds: Rectangle,
+    cursor: mouse::Cursor,
+) -> Option<Message> {
+    if !state.as_ref().is_some_and(|state| state.is::<P::State>()) {
+        *state = Some(Box::<P::State>::default());
+    }
+    let state = state.as_mut().unwrap().downcast_mut::<P::State>().unwrap();
+
+    program.update(state, event, bounds, cursor).1
+}
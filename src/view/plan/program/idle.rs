@@ -1,8 +1,14 @@
-use iced::widget::canvas::{Program, Frame};
+use iced::event::Status;
+use iced::widget::canvas::{Event, Frame, Program, Stroke};
+use iced::{keyboard, mouse, Color};
 
 use crate::controller::Controller;
 use crate::message::Message;
 use crate::view;
+use crate::view::plan::screen_to_world;
+
+const GROUP_COLOR: Color = Color::from_rgb(0.9, 0.6, 0.1);
+const SELECTION_COLOR: Color = Color::from_rgb(0.1, 0.3, 0.9);
 
 pub struct Idle<'a> {
     controller: &'a Controller,
@@ -23,13 +29,211 @@ impl Program<Message> for Idle<'_> {
         renderer: &iced::Renderer<iced::Theme>,
         _theme: &iced::Theme,
         bounds: iced::Rectangle,
-        _cursor: iced::advanced::mouse::Cursor,
+        cursor: iced::advanced::mouse::Cursor,
     ) -> Vec<<iced::Renderer<iced::Theme> as iced::widget::canvas::Renderer>::Geometry> {
 
         let mut frame = Frame::new(renderer, bounds.size());
 
+        view::plan::draw_room(&mut frame, self.controller.plan(), self.controller.scale());
         view::plan::draw_shapes(&mut frame, self.controller.plan().iter(), self.controller.scale());
+        view::plan::draw_dimensions(&mut frame, self.controller.plan(), self.controller.scale(), &[]);
+
+        for shape_id in self.controller.selection() {
+            if let Some(shape) = self.controller.plan().get_shape(shape_id) {
+                let path = view::plan::draw_shape(shape, self.controller.scale());
+                frame.stroke(
+                    &path,
+                    Stroke::default().with_color(SELECTION_COLOR).with_width(3.0),
+                );
+            }
+        }
+
+        // Copies of the array being prepared, the ones overlapping other
+        // shapes will be skipped
+        for (copy, fits) in self.controller.array_preview() {
+            for shape in &copy {
+                view::plan::draw_preview_shape(&mut frame, shape, fits, self.controller.scale());
+            }
+        }
+
+        // Layout proposed by the optimizer
+        if let Some(preview) = self.controller.optimization_preview() {
+            for (_, _, shape) in &preview.shapes {
+                view::plan::draw_preview_shape(&mut frame, shape, true, self.controller.scale());
+            }
+        }
+
+        if let Some(screen_cursor_pos) = cursor.position_in(bounds) {
+            let world_cursor_pos = screen_to_world(screen_cursor_pos, self.controller.scale());
+            if let Some((shape_id, shape)) = self.controller.plan().get_shape_at(&world_cursor_pos) {
+                let metadata = self.controller.plan().get_metadata(*shape_id);
+                let name = metadata
+                    .and_then(|metadata| metadata.name.as_deref())
+                    .unwrap_or(shape.name());
+                let category = metadata.and_then(|metadata| metadata.category.as_ref());
+                let block = self
+                    .controller
+                    .plan()
+                    .get_block_member(*shape_id)
+                    .and_then(|member| self.controller.plan().get_block(member.block_id));
+
+                // The members of a group are highlighted together
+                let group = self.controller.plan().group_of(*shape_id);
+                if group.len() > 1 {
+                    for member in group.iter().filter_map(|id| self.controller.plan().get_shape(*id)) {
+                        let path = view::plan::draw_shape(member, self.controller.scale());
+                        frame.stroke(
+                            &path,
+                            Stroke::default().with_color(GROUP_COLOR).with_width(2.0),
+                        );
+                    }
+                }
+
+                let lines: Vec<_> = std::iter::once(name.to_string())
+                    .chain(category.map(|category| format!("Category: {category}")))
+                    .chain(block.map(|block| format!("Block: {}", block.name())))
+                    .chain((group.len() > 1).then(|| format!("Group of {} shapes", group.len())))
+                    .chain(std::iter::once(view::plan::format_dimensions(shape)))
+                    .collect();
+                view::plan::draw_label(&mut frame, screen_cursor_pos, &lines);
+            }
+        }
 
         vec![frame.into_geometry()]
     }
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: Event,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> (Status, Option<Message>) {
+        let cursor_message = view::plan::track_cursor(self.controller, &event, bounds);
+        let Some(screen_cursor_pos) = cursor.position_in(bounds) else {
+            return (Status::Ignored, cursor_message);
+        };
+
+        match event {
+            // Clicking a shape selects its whole group, clicking
+            // outside of the shapes clears the selection
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let world_cursor_pos = screen_to_world(screen_cursor_pos, self.controller.scale());
+                let plan = self.controller.plan();
+                let selection = plan
+                    .get_shape_at(&world_cursor_pos)
+                    .map(|(shape_id, _)| plan.group_of(*shape_id))
+                    .unwrap_or_default();
+
+                (Status::Captured, Some(Message::Select(selection)))
+            }
+            // Shortcuts only apply while the cursor is over the plan,
+            // so they don't conflict with the text inputs
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code,
+                modifiers,
+            }) => {
+                let message = match key_code {
+                    keyboard::KeyCode::C if modifiers.command() => Message::CopyButton,
+                    keyboard::KeyCode::X if modifiers.command() => Message::CutButton,
+                    keyboard::KeyCode::V if modifiers.command() => Message::PasteButton,
+                    keyboard::KeyCode::D if modifiers.command() => Message::DuplicateButton,
+                    keyboard::KeyCode::Delete => Message::DeleteButton,
+                    _ => return (Status::Ignored, cursor_message),
+                };
+
+                (Status::Captured, Some(message))
+            }
+            _ => (Status::Ignored, cursor_message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use iced::keyboard;
+
+    use crate::controller::clipboard::ClipboardAction;
+    use crate::message::Message;
+    use crate::model::shape::{Circle, Rectangle, Shape};
+    use crate::view::plan::program::harness::Harness;
+
+    #[test]
+    fn click_selects_whole_group() {
+        let mut harness = Harness::default();
+        harness.send(Message::AddShape(Rectangle::new((0, 0).into(), 4, 2).into()));
+        harness.send(Message::AddShape(Circle::new((2, 5).into(), 1).into()));
+        harness.send(Message::AddShape(Circle::new((20, 20).into(), 1).into()));
+        harness.send(Message::GroupButton);
+        harness.drag((0, 0), (6, 6));
+
+        harness.click((2, 5));
+        assert_eq!(harness.controller.selection().len(), 2);
+
+        harness.click((20, 20));
+        assert_eq!(harness.controller.selection().len(), 1);
+
+        harness.click((30, 30));
+        assert!(harness.controller.selection().is_empty());
+    }
+
+    #[test]
+    fn copy_and_paste_at_cursor() {
+        let mut harness = Harness::default();
+        harness.send(Message::AddShape(Rectangle::new((0, 0).into(), 4, 2).into()));
+        harness.click((1, 1));
+        harness.press_key(keyboard::KeyCode::C, keyboard::Modifiers::CTRL);
+
+        let Some(ClipboardAction::Write(content)) = harness.controller.take_clipboard_action()
+        else {
+            panic!("the selection is not copied");
+        };
+
+        // The cursor is over the copied shape, the copy is placed next to it
+        harness.send(Message::Paste(Some(content.clone())));
+        harness.move_to((10, 10));
+        harness.send(Message::Paste(Some(content)));
+
+        let plan = harness.controller.plan();
+        assert_eq!(plan.iter().count(), 3);
+        let (ids, shapes): (Vec<_>, Vec<_>) = plan.iter().cloned().unzip();
+        assert!(plan.are_disjoint(&shapes, &ids));
+        let pasted: Shape = Rectangle::new((10, 10).into(), 4, 2).into();
+        assert!(shapes.contains(&pasted));
+        assert_eq!(harness.controller.selection().len(), 1);
+    }
+
+    #[test]
+    fn cut_duplicate_and_delete() {
+        let mut harness = Harness::default();
+        harness.send(Message::AddShape(Rectangle::new((0, 0).into(), 4, 2).into()));
+        harness.click((1, 1));
+
+        harness.send(Message::DuplicateButton);
+        assert_eq!(harness.controller.plan().iter().count(), 2);
+
+        // The duplicate is selected, deleting it leaves the original
+        harness.press_key(keyboard::KeyCode::Delete, keyboard::Modifiers::empty());
+        let shapes: Vec<_> = harness.controller.plan().iter().map(|(_, s)| s.clone()).collect();
+        assert_eq!(shapes, [Rectangle::new((0, 0).into(), 4, 2).into()]);
+
+        harness.click((1, 1));
+        harness.send(Message::CutButton);
+        assert_eq!(harness.controller.plan().iter().count(), 0);
+        assert!(harness.controller.take_clipboard_action().is_some());
+
+        harness.send(Message::Undo);
+        assert_eq!(harness.controller.plan().iter().count(), 1);
+    }
+
+    #[test]
+    fn cursor_position_is_published() {
+        let mut harness = Harness::default();
+
+        harness.move_to((12, 7));
+        harness.move_to((12, 7));
+
+        assert_eq!(harness.controller.cursor(), Some((12, 7).into()));
+        assert_eq!(harness.messages.len(), 1);
+    }
 }
@@ -0,0 +1,227 @@
+use iced::event::Status;
+use iced::widget::canvas::{Event, Frame, Path, Program, Stroke};
+use iced::{mouse, Color, Point};
+
+use crate::controller::state::MeasureKind;
+use crate::controller::Controller;
+use crate::message::Message;
+use crate::model::measure;
+use crate::model::plan::ShapeId;
+use crate::model::vec2::Vec2;
+use crate::view;
+use crate::view::plan::{screen_to_world, world_to_screen};
+
+const MEASURE_COLOR: Color = Color::from_rgb(0.1, 0.3, 0.9);
+const POINT_MARKER_RADIUS: f32 = 3.0;
+
+#[derive(Debug, Default)]
+pub struct State {
+    points: Vec<Vec2>,
+    shapes: Vec<ShapeId>,
+}
+
+impl State {
+    fn is_empty(&self) -> bool {
+        self.points.is_empty() && self.shapes.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.points.clear();
+        self.shapes.clear();
+    }
+}
+
+#[derive(Debug)]
+pub struct Measure<'a> {
+    controller: &'a Controller,
+    kind: MeasureKind,
+}
+
+impl<'a> Measure<'a> {
+    pub fn new(controller: &'a Controller, kind: MeasureKind) -> Self {
+        Self { controller, kind }
+    }
+
+    /// Points measured, including the cursor as the next point
+    /// while the measure is not complete
+    fn measured_points(&self, state: &State, world_cursor_pos: Option<Vec2>) -> Vec<Vec2> {
+        let mut points = state.points.clone();
+        let complete = matches!(self.kind, MeasureKind::Distance) && points.len() == 2;
+
+        if let Some(world_cursor_pos) = world_cursor_pos.filter(|_| !complete) {
+            points.push(world_cursor_pos);
+        }
+
+        points
+    }
+
+    fn draw_points(&self, frame: &mut Frame, points: &[Vec2]) {
+        let scale = self.controller.scale();
+        let closed = matches!(self.kind, MeasureKind::Area);
+
+        let path = Path::new(|builder| {
+            let mut screen_points = points.iter().map(|point| world_to_screen(*point, scale));
+            if let Some(first) = screen_points.next() {
+                builder.move_to(first);
+                screen_points.for_each(|point| builder.line_to(point));
+                if closed {
+                    builder.close();
+                }
+            }
+        });
+
+        if closed {
+            frame.fill(&path, Color { a: 0.2, ..MEASURE_COLOR });
+        }
+        frame.stroke(
+            &path,
+            Stroke::default().with_color(MEASURE_COLOR).with_width(2.0),
+        );
+
+        for point in points {
+            let marker = Path::circle(world_to_screen(*point, scale), POINT_MARKER_RADIUS);
+            frame.fill(&marker, MEASURE_COLOR);
+        }
+    }
+
+    fn draw_selected_shapes(&self, frame: &mut Frame, state: &State) {
+        let plan = self.controller.plan();
+        for shape in state.shapes.iter().filter_map(|id| plan.get_shape(*id)) {
+            let path = view::plan::draw_shape(shape, self.controller.scale());
+            frame.stroke(
+                &path,
+                Stroke::default().with_color(MEASURE_COLOR).with_width(3.0),
+            );
+        }
+    }
+
+    fn measure_label(&self, state: &State, points: &[Vec2]) -> Option<String> {
+        match self.kind {
+            MeasureKind::Distance if points.len() == 2 => Some(format!(
+                "Distance: {:.2}",
+                points[0].exact_distance(&points[1])
+            )),
+            MeasureKind::Polyline if points.len() >= 2 => Some(format!(
+                "Length: {:.2}",
+                measure::polyline_length(points)
+            )),
+            MeasureKind::Area if points.len() >= 3 => Some(format!(
+                "Area: {:.2}",
+                measure::polygon_area(points)
+            )),
+            MeasureKind::ShapeDistance => {
+                let plan = self.controller.plan();
+                let [shape1, shape2] = state.shapes.as_slice() else {
+                    return None;
+                };
+                let (shape1, shape2) = (plan.get_shape(*shape1)?, plan.get_shape(*shape2)?);
+
+                Some(format!("Nearest distance: {:.2}", shape1.distance(shape2)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Program<Message> for Measure<'_> {
+    type State = State;
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &iced::Renderer<iced::Theme>,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> Vec<<iced::Renderer<iced::Theme> as iced::widget::canvas::Renderer>::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let scale = self.controller.scale();
+
+        view::plan::draw_room(&mut frame, self.controller.plan(), scale);
+        view::plan::draw_shapes(&mut frame, self.controller.plan().iter(), scale);
+        view::plan::draw_dimensions(&mut frame, self.controller.plan(), scale, &[]);
+
+        let screen_cursor_pos = cursor.position_in(bounds);
+        let world_cursor_pos = screen_cursor_pos.map(|pos| screen_to_world(pos, scale));
+
+        let points = match self.kind {
+            MeasureKind::ShapeDistance => {
+                self.draw_selected_shapes(&mut frame, state);
+                vec![]
+            }
+            _ => {
+                let points = self.measured_points(state, world_cursor_pos);
+                self.draw_points(&mut frame, &points);
+                points
+            }
+        };
+
+        if let Some(label) = self.measure_label(state, &points) {
+            // The label follows the cursor and stays on the last point
+            // when the cursor leaves the plan
+            let anchor = screen_cursor_pos
+                .or_else(|| points.last().map(|point| world_to_screen(*point, scale)))
+                .unwrap_or(Point::ORIGIN);
+            view::plan::draw_label(&mut frame, anchor, &[label]);
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: Event,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> (Status, Option<Message>) {
+        let cursor_message = view::plan::track_cursor(self.controller, &event, bounds);
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let Some(screen_cursor_pos) = cursor.position_in(bounds) else {
+                    return (Status::Ignored, None);
+                };
+
+                let world_cursor_pos = screen_to_world(screen_cursor_pos, self.controller.scale());
+                match self.kind {
+                    MeasureKind::Distance => {
+                        // A third click starts a new measure
+                        if state.points.len() == 2 {
+                            state.points.clear();
+                        }
+                        state.points.push(world_cursor_pos);
+                    }
+                    MeasureKind::Polyline | MeasureKind::Area => {
+                        state.points.push(world_cursor_pos)
+                    }
+                    MeasureKind::ShapeDistance => {
+                        if let Some((shape_id, _)) =
+                            self.controller.plan().get_shape_at(&world_cursor_pos)
+                        {
+                            if state.shapes.contains(shape_id) {
+                                state.shapes.retain(|id| id != shape_id);
+                            } else {
+                                if state.shapes.len() == 2 {
+                                    state.shapes.remove(0);
+                                }
+                                state.shapes.push(*shape_id);
+                            }
+                        }
+                    }
+                }
+
+                (Status::Captured, None)
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                if state.is_empty() {
+                    (Status::Captured, Some(Message::Cancel))
+                } else {
+                    state.clear();
+                    (Status::Captured, None)
+                }
+            }
+            _ => (Status::Ignored, cursor_message),
+        }
+    }
+}
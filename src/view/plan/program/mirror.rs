@@ -0,0 +1,148 @@
+use iced::event::Status;
+use iced::widget::canvas::{Event, Frame, Path, Program, Stroke};
+use iced::{mouse, Color, Point};
+
+use crate::controller::Controller;
+use crate::message::Message;
+use crate::model::shape::Flip;
+use crate::model::vec2::Vec2;
+use crate::view;
+use crate::view::plan::{screen_to_world, world_to_screen};
+
+const AXIS_COLOR: Color = Color::from_rgb(0.1, 0.3, 0.9);
+
+/// Mirror the selection about the axis under the cursor
+#[derive(Debug)]
+pub struct Mirror<'a> {
+    controller: &'a Controller,
+    flip: Flip,
+}
+
+impl<'a> Mirror<'a> {
+    pub fn new(controller: &'a Controller, flip: Flip) -> Self {
+        Self { controller, flip }
+    }
+
+    /// Coordinate of the axis going through `world_pos`
+    fn axis(&self, world_pos: Vec2) -> i32 {
+        match self.flip {
+            Flip::Horizontal => world_pos.x,
+            Flip::Vertical => world_pos.y,
+        }
+    }
+}
+
+impl Program<Message> for Mirror<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer<iced::Theme>,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> Vec<<iced::Renderer<iced::Theme> as iced::widget::canvas::Renderer>::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let plan = self.controller.plan();
+        let scale = self.controller.scale();
+
+        let Some(screen_cursor_pos) = cursor.position_in(bounds) else {
+            view::plan::draw_room(&mut frame, plan, scale);
+            view::plan::draw_shapes(&mut frame, plan.iter(), scale);
+            view::plan::draw_dimensions(&mut frame, plan, scale, &[]);
+            return vec![frame.into_geometry()];
+        };
+
+        let axis = self.axis(screen_to_world(screen_cursor_pos, scale));
+        let preview = self.controller.mirrored_selection(self.flip, 2 * axis);
+        let (ids, shapes): (Vec<_>, Vec<_>) = preview.iter().cloned().unzip();
+        let fits = plan.are_disjoint(&shapes, &ids);
+
+        view::plan::draw_room(&mut frame, plan, scale);
+        view::plan::draw_shapes(
+            &mut frame,
+            plan.iter().filter(|(id, _)| !ids.contains(id)),
+            scale,
+        );
+        view::plan::draw_dimensions(&mut frame, plan, scale, &preview);
+        for shape in &shapes {
+            view::plan::draw_preview_shape(&mut frame, shape, fits, scale);
+        }
+
+        let axis_line = match self.flip {
+            Flip::Horizontal => {
+                let x = world_to_screen(Vec2::new(axis, 0), scale).x;
+                Path::line(Point::new(x, 0.0), Point::new(x, bounds.height))
+            }
+            Flip::Vertical => {
+                let y = world_to_screen(Vec2::new(0, axis), scale).y;
+                Path::line(Point::new(0.0, y), Point::new(bounds.width, y))
+            }
+        };
+        frame.stroke(&axis_line, Stroke::default().with_color(AXIS_COLOR).with_width(2.0));
+
+        vec![frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: Event,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> (Status, Option<Message>) {
+        let cursor_message = view::plan::track_cursor(self.controller, &event, bounds);
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let Some(screen_cursor_pos) = cursor.position_in(bounds) else {
+                    return (Status::Ignored, None);
+                };
+
+                let world_cursor_pos = screen_to_world(screen_cursor_pos, self.controller.scale());
+                let message = Message::Mirror(self.flip, self.axis(world_cursor_pos));
+                (Status::Captured, Some(message))
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                (Status::Captured, Some(Message::Cancel))
+            }
+            _ => (Status::Ignored, cursor_message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::message::Message;
+    use crate::model::shape::{Circle, Flip, Rectangle};
+    use crate::view::plan::program::harness::Harness;
+
+    #[test]
+    fn mirror_about_clicked_axis() {
+        let mut harness = Harness::default();
+        harness.send(Message::AddShape(Rectangle::new((2, 2).into(), 4, 2).into()));
+        harness.send(Message::AddShape(Circle::new((4, 6).into(), 1).into()));
+        harness.send(Message::AddShape(Rectangle::new((15, 3).into(), 2, 2).into()));
+        harness.send(Message::SelectButton);
+        harness.drag((1, 1), (8, 8));
+
+        // Mirroring about x = 9 would put the first rectangle on the last one
+        harness.send(Message::MirrorButton(Flip::Horizontal));
+        harness.click((9, 8));
+        assert!(harness.controller.notice().is_some());
+        harness.send(Message::MirrorButton(Flip::Horizontal));
+        harness.click((8, 8));
+
+        let plan = harness.controller.plan();
+        let rect = Rectangle::new((10, 2).into(), 4, 2).into();
+        let circle = Circle::new((12, 6).into(), 1).into();
+        assert!(plan.iter().any(|(_, shape)| *shape == rect));
+        assert!(plan.iter().any(|(_, shape)| *shape == circle));
+        assert!(harness.controller.idle());
+
+        harness.send(Message::Undo);
+        let circle = Circle::new((4, 6).into(), 1).into();
+        assert!(harness.controller.plan().iter().any(|(_, shape)| *shape == circle));
+    }
+}
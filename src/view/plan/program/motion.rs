@@ -0,0 +1,141 @@
+use iced::event::Status;
+use iced::widget::canvas::{Event, Frame, Path, Program, Stroke};
+use iced::{mouse, Color};
+
+use crate::controller::Controller;
+use crate::message::Message;
+use crate::model::motion;
+use crate::view;
+use crate::view::plan::{screen_to_world, world_to_screen};
+
+const SELECTION_COLOR: Color = Color::from_rgb(0.1, 0.3, 0.9);
+const PATH_COLOR: Color = Color::from_rgb(0.9, 0.5, 0.0);
+
+/// Look for paths moving the selected shape to the spots clicked on the plan,
+/// and draw the last one found
+#[derive(Debug)]
+pub struct Motion<'a> {
+    controller: &'a Controller,
+}
+
+impl<'a> Motion<'a> {
+    pub fn new(controller: &'a Controller) -> Self {
+        Self { controller }
+    }
+}
+
+impl Program<Message> for Motion<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer<iced::Theme>,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> Vec<<iced::Renderer<iced::Theme> as iced::widget::canvas::Renderer>::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let plan = self.controller.plan();
+        let scale = self.controller.scale();
+
+        view::plan::draw_room(&mut frame, plan, scale);
+        view::plan::draw_shapes(&mut frame, plan.iter(), scale);
+        view::plan::draw_dimensions(&mut frame, plan, scale, &[]);
+
+        let selection = self.controller.selection();
+        let Some((shape_id, shape)) = selection
+            .first()
+            .and_then(|shape_id| Some((*shape_id, plan.get_shape(*shape_id)?)))
+        else {
+            return vec![frame.into_geometry()];
+        };
+        let path = view::plan::draw_shape(shape, scale);
+        frame.stroke(&path, Stroke::default().with_color(SELECTION_COLOR).with_width(3.0));
+
+        if let Some(waypoints) = self.controller.motion_path() {
+            let line = Path::new(|builder| {
+                for (i, waypoint) in waypoints.iter().enumerate() {
+                    let point = world_to_screen(motion::center(waypoint), scale);
+                    if i == 0 {
+                        builder.move_to(point);
+                    } else {
+                        builder.line_to(point);
+                    }
+                }
+            });
+            frame.stroke(&line, Stroke::default().with_color(PATH_COLOR).with_width(2.0));
+
+            for waypoint in waypoints.iter().skip(1) {
+                let path = view::plan::draw_shape(waypoint, scale);
+                frame.stroke(&path, Stroke::default().with_color(PATH_COLOR));
+            }
+        }
+
+        if let Some(screen_cursor_pos) = cursor.position_in(bounds) {
+            let world_cursor_pos = screen_to_world(screen_cursor_pos, scale);
+            let target = motion::centered_at(shape, world_cursor_pos);
+            view::plan::draw_preview(&mut frame, plan, &target, &[shape_id], scale);
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: Event,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> (Status, Option<Message>) {
+        let cursor_message = view::plan::track_cursor(self.controller, &event, bounds);
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let Some(screen_cursor_pos) = cursor.position_in(bounds) else {
+                    return (Status::Ignored, None);
+                };
+
+                let world_cursor_pos = screen_to_world(screen_cursor_pos, self.controller.scale());
+                (Status::Captured, Some(Message::PlanMotion(world_cursor_pos)))
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                (Status::Captured, Some(Message::Cancel))
+            }
+            _ => (Status::Ignored, cursor_message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::message::Message;
+    use crate::model::shape::Rectangle;
+    use crate::view::plan::program::harness::Harness;
+
+    #[test]
+    fn path_is_found_for_clicked_spot() {
+        let mut harness = Harness::default();
+        harness.send(Message::SetRoomButton);
+        harness.drag((0, 0), (20, 17));
+        harness.send(Message::AddShape(Rectangle::new((2, 2).into(), 2, 6).into()));
+        // Wall with a gap 3 units high
+        harness.send(Message::AddShape(Rectangle::new((8, 0).into(), 2, 4).into()));
+        harness.send(Message::AddShape(Rectangle::new((8, 7).into(), 2, 10).into()));
+        harness.click((3, 3));
+        assert_eq!(harness.controller.selection().len(), 1);
+
+        harness.send(Message::MotionButton(false));
+        harness.click((16, 5));
+        assert!(harness.controller.motion_path().is_none());
+        assert!(harness.controller.notice().is_some());
+
+        harness.send(Message::Cancel);
+        harness.send(Message::MotionButton(true));
+        harness.click((16, 5));
+        let path = harness.controller.motion_path().unwrap();
+        assert!(path.len() > 2);
+        // The query leaves the plan untouched
+        assert_eq!(harness.controller.history().len(), 4);
+    }
+}
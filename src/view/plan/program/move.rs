@@ -1,13 +1,14 @@
 use std::mem;
 
 use iced::event::Status;
-use iced::widget::canvas::{Event, Frame, Program, Stroke};
-use iced::{mouse, Color, Point};
+use iced::widget::canvas::{Event, Frame, Program};
+use iced::{mouse, Point};
 
 use crate::controller::Controller;
 use crate::message::Message;
 use crate::model::plan::ShapeId;
 use crate::model::shape::Shape;
+use crate::model::vec2::Vec2;
 use crate::view;
 use crate::view::plan::screen_to_world;
 
@@ -16,11 +17,39 @@ pub enum State {
     #[default]
     Chosing,
     Moving {
-        shape: Shape,
-        shape_id: ShapeId,
+        /// Initial position of the picked shape, which follows the cursor
+        anchor: Vec2,
+        /// Translation of the shapes from their initial position
+        offset: Vec2,
+        /// Picked shape along with the other members of its group,
+        /// at their initial position
+        shapes: Vec<(ShapeId, Shape)>,
     },
 }
 
+impl State {
+    /// The controller cancels the gesture when the history is used during a move,
+    /// the state is then stale and must be handled as `Chosing`
+    fn is_cancelled(&self, controller: &Controller) -> bool {
+        matches!(self, State::Moving { .. }) && !controller.gesture_in_progress()
+    }
+
+    /// Shapes being moved at their current position
+    fn preview(&self) -> Vec<(ShapeId, Shape)> {
+        match self {
+            State::Chosing => Vec::new(),
+            State::Moving { offset, shapes, .. } => shapes
+                .iter()
+                .map(|(id, shape)| {
+                    let mut shape = shape.clone();
+                    shape.translate(*offset);
+                    (*id, shape)
+                })
+                .collect(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Move<'a> {
     controller: &'a Controller,
@@ -45,23 +74,35 @@ impl Program<Message> for Move<'_> {
     ) -> Vec<<iced::Renderer<iced::Theme> as iced::widget::canvas::Renderer>::Geometry> {
         let mut frame = Frame::new(renderer, bounds.size());
 
-        let filter_id = match state {
-            State::Chosing => None,
-            State::Moving { shape_id, .. } => Some(shape_id),
+        let preview = if state.is_cancelled(self.controller) {
+            Vec::new()
+        } else {
+            state.preview()
         };
+        let moving_ids: Vec<_> = preview.iter().map(|(id, _)| *id).collect();
+
         let shapes = self
             .controller
             .plan()
             .iter()
-            .filter(|(id, _)| Some(id) != filter_id);
+            .filter(|(id, _)| !moving_ids.contains(id));
+        view::plan::draw_room(&mut frame, self.controller.plan(), self.controller.scale());
         view::plan::draw_shapes(&mut frame, shapes, self.controller.scale());
 
-        if let State::Moving { shape, .. } = state {
-            let path = view::plan::draw_shape(shape, self.controller.scale());
+        view::plan::draw_dimensions(
+            &mut frame,
+            self.controller.plan(),
+            self.controller.scale(),
+            &preview,
+        );
 
-            frame.stroke(
-                &path,
-                Stroke::default().with_color(Color::BLACK).with_width(2.0),
+        for (_, shape) in &preview {
+            view::plan::draw_preview(
+                &mut frame,
+                self.controller.plan(),
+                shape,
+                &moving_ids,
+                self.controller.scale(),
             );
         }
 
@@ -75,28 +116,44 @@ impl Program<Message> for Move<'_> {
         bounds: iced::Rectangle,
         cursor: iced::advanced::mouse::Cursor,
     ) -> (Status, Option<Message>) {
+        let cursor_message = view::plan::track_cursor(self.controller, &event, bounds);
+
+        if state.is_cancelled(self.controller) {
+            *state = State::Chosing;
+        }
+
         match (&mut *state, event) {
             (State::Chosing, Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))) => {
                 let Some(screen_cursor_pos) = cursor.position_in(bounds) else {
                         return (Status::Ignored, None);
                     };
 
+                let plan = self.controller.plan();
                 let world_cursor_pos = screen_to_world(screen_cursor_pos, self.controller.scale());
-                if let Some((shape_id, shape)) =
-                    self.controller.plan().get_shape_at(&world_cursor_pos)
-                {
-                    let mut shape = shape.clone();
-                    shape.move_to(world_cursor_pos);
+                if let Some((shape_id, shape)) = plan.get_shape_at(&world_cursor_pos) {
+                    // The whole group of the picked shape moves with it
+                    let shapes = plan
+                        .group_of(*shape_id)
+                        .into_iter()
+                        .filter_map(|id| Some((id, plan.get_shape(id)?.clone())))
+                        .collect();
                     *state = State::Moving {
-                        shape,
-                        shape_id: *shape_id,
-                    }
+                        anchor: shape.pos(),
+                        offset: world_cursor_pos - shape.pos(),
+                        shapes,
+                    };
+
+                    return (Status::Captured, Some(Message::GestureStarted));
                 }
 
                 (Status::Captured, None)
             }
             (
-                State::Moving { ref mut shape, shape_id },
+                State::Moving {
+                    anchor,
+                    ref mut offset,
+                    ..
+                },
                 Event::Mouse(mouse::Event::CursorMoved {
                     position: absolute_pos,
                 }),
@@ -104,34 +161,145 @@ impl Program<Message> for Move<'_> {
                 if bounds.contains(absolute_pos) {
                     let relative_pos = Point::ORIGIN + (absolute_pos - bounds.position());
                     let new_pos = screen_to_world(relative_pos, self.controller.scale());
-                    let mut new_shape = shape.clone();
-                    new_shape.move_to(new_pos);
-
-                    if self.controller.plan().is_disjoint(&new_shape, Some(*shape_id)) {
-                        *shape = new_shape;
-                    }
+                    *offset = new_pos - *anchor;
 
-                    (Status::Captured, None)
+                    (Status::Captured, cursor_message)
                 } else {
-                    (Status::Ignored, None)
+                    (Status::Ignored, cursor_message)
                 }
             }
             (
                 State::Moving { .. },
                 Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)),
             ) => {
-                let State::Moving { shape, shape_id } =
-                        mem::replace(state, State::Chosing) else { unreachable!() };
+                let mut preview = mem::take(state).preview();
+                let ids: Vec<_> = preview.iter().map(|(id, _)| *id).collect();
+                let shapes: Vec<_> = preview.iter().map(|(_, shape)| shape.clone()).collect();
 
-                (
-                    Status::Captured,
-                    Some(Message::UpdateShape(shape_id, shape)),
-                )
+                // Dropping the shapes on an invalid position cancels the move
+                if !self.controller.plan().are_disjoint(&shapes, &ids) {
+                    return (Status::Captured, Some(Message::GestureEnded));
+                }
+
+                let message = match preview.len() {
+                    1 => {
+                        let (shape_id, shape) = preview.remove(0);
+                        Message::UpdateShape(shape_id, shape)
+                    }
+                    _ => Message::UpdateShapes(preview),
+                };
+
+                (Status::Captured, Some(message))
             }
             (_, Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right))) => {
                 (Status::Captured, Some(Message::Cancel))
             }
-            _ => (Status::Ignored, None),
+            _ => (Status::Ignored, cursor_message),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use iced::mouse;
+
+    use crate::message::Message;
+    use crate::model::shape::{Rectangle, Shape};
+    use crate::view::plan::program::harness::Harness;
+
+    fn harness_with_rectangles() -> Harness {
+        let mut harness = Harness::default();
+        harness.send(Message::AddShape(
+            Rectangle::new((0, 0).into(), 4, 4).into(),
+        ));
+        harness.send(Message::AddShape(
+            Rectangle::new((10, 0).into(), 4, 4).into(),
+        ));
+        harness.send(Message::MoveButton);
+        harness
+    }
+
+    fn contains(harness: &Harness, shape: Rectangle) -> bool {
+        let shape = Shape::from(shape);
+        harness.controller.plan().iter().any(|(_, s)| *s == shape)
+    }
+
+    #[test]
+    fn drag_moves_shape() {
+        let mut harness = harness_with_rectangles();
+
+        harness.drag((1, 1), (0, 20));
+
+        assert!(contains(&harness, Rectangle::new((0, 20).into(), 4, 4)));
+        assert!(harness
+            .messages
+            .iter()
+            .any(|message| matches!(message, Message::UpdateShape(..))));
+    }
+
+    #[test]
+    fn drag_moves_whole_group() {
+        let mut harness = harness_with_rectangles();
+        harness.send(Message::AddShape(Rectangle::new((0, 6).into(), 2, 2).into()));
+        harness.send(Message::GroupButton);
+        harness.drag((0, 0), (4, 8));
+        harness.send(Message::MoveButton);
+
+        harness.drag((1, 7), (20, 20));
+
+        assert!(contains(&harness, Rectangle::new((20, 20).into(), 2, 2)));
+        assert!(contains(&harness, Rectangle::new((20, 14).into(), 4, 4)));
+        assert!(contains(&harness, Rectangle::new((10, 0).into(), 4, 4)));
+
+        // The group is moved in a single step
+        harness.send(Message::Undo);
+        assert!(contains(&harness, Rectangle::new((0, 0).into(), 4, 4)));
+        assert!(contains(&harness, Rectangle::new((0, 6).into(), 2, 2)));
+    }
+
+    #[test]
+    fn group_collision_cancels_move() {
+        let mut harness = harness_with_rectangles();
+        harness.send(Message::AddShape(Rectangle::new((0, 6).into(), 2, 2).into()));
+        harness.send(Message::GroupButton);
+        harness.drag((0, 0), (4, 8));
+        harness.send(Message::MoveButton);
+
+        // The small rectangle would fit but the other member would overlap
+        harness.drag((0, 6), (10, 8));
+
+        assert!(contains(&harness, Rectangle::new((0, 6).into(), 2, 2)));
+        assert!(!harness.controller.gesture_in_progress());
+    }
+
+    #[test]
+    fn drop_on_invalid_position_cancels_move() {
+        let mut harness = harness_with_rectangles();
+
+        harness.drag((1, 1), (9, 0));
+
+        assert!(contains(&harness, Rectangle::new((0, 0).into(), 4, 4)));
+        assert!(!harness.controller.gesture_in_progress());
+        assert!(!harness.controller.can_redo());
+    }
+
+    #[test]
+    fn undo_while_dragging_cancels_move() {
+        let mut harness = harness_with_rectangles();
+
+        harness.move_to((1, 1));
+        harness.press(mouse::Button::Left);
+        harness.move_to((0, 20));
+        harness.send(Message::Undo);
+        harness.release(mouse::Button::Left);
+
+        assert!(contains(&harness, Rectangle::new((0, 0).into(), 4, 4)));
+        assert_eq!(harness.controller.plan().iter().count(), 2);
+
+        // Undoing again removes the last shape while staying in the tool
+        harness.send(Message::Undo);
+        harness.drag((1, 1), (0, 20));
+        assert_eq!(harness.controller.plan().iter().count(), 1);
+        assert!(contains(&harness, Rectangle::new((0, 20).into(), 4, 4)));
+    }
+}
@@ -0,0 +1,138 @@
+use iced::event::Status;
+use iced::widget::canvas::{Event, Frame, Program};
+use iced::mouse;
+
+use crate::controller::Controller;
+use crate::message::Message;
+use crate::model::catalog::CatalogEntry;
+use crate::view;
+use crate::view::plan::screen_to_world;
+
+/// Place items of the catalog, whose shape is already sized
+#[derive(Debug)]
+pub struct Place<'a> {
+    controller: &'a Controller,
+    entry: &'a CatalogEntry,
+}
+
+impl<'a> Place<'a> {
+    pub fn new(controller: &'a Controller, entry: &'a CatalogEntry) -> Self {
+        Self { controller, entry }
+    }
+}
+
+impl Program<Message> for Place<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer<iced::Theme>,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> Vec<<iced::Renderer<iced::Theme> as iced::widget::canvas::Renderer>::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let plan = self.controller.plan();
+        let scale = self.controller.scale();
+
+        view::plan::draw_room(&mut frame, plan, scale);
+        view::plan::draw_shapes(&mut frame, plan.iter(), scale);
+        view::plan::draw_dimensions(&mut frame, plan, scale, &[]);
+
+        if let Some(screen_cursor_pos) = cursor.position_in(bounds) {
+            let world_cursor_pos = screen_to_world(screen_cursor_pos, scale);
+            let shape = self.entry.shape_at(world_cursor_pos);
+
+            view::plan::draw_preview(&mut frame, plan, &shape, &[], scale);
+            view::plan::draw_label(
+                &mut frame,
+                screen_cursor_pos,
+                &[
+                    self.entry.name.clone(),
+                    view::plan::format_dimensions(&shape),
+                    view::plan::format_position(world_cursor_pos),
+                ],
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: Event,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> (Status, Option<Message>) {
+        let cursor_message = view::plan::track_cursor(self.controller, &event, bounds);
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let Some(screen_cursor_pos) = cursor.position_in(bounds) else {
+                    return (Status::Ignored, None);
+                };
+
+                let world_cursor_pos = screen_to_world(screen_cursor_pos, self.controller.scale());
+                let shape = self.entry.shape_at(world_cursor_pos);
+                let message = self
+                    .controller
+                    .plan()
+                    .is_disjoint(&shape, None)
+                    .then(|| Message::AddItem(shape, self.entry.metadata()));
+
+                (Status::Captured, message)
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                (Status::Captured, Some(Message::Cancel))
+            }
+            _ => (Status::Ignored, cursor_message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::message::Message;
+    use crate::model::catalog::Catalog;
+    use crate::model::shape::{Rectangle, Shape};
+    use crate::view::plan::program::harness::Harness;
+
+    #[test]
+    fn place_catalog_items() {
+        let catalog = r#"[{
+            "name": "Desk",
+            "shape": { "Rectangle": { "width": 8, "height": 4 } },
+            "metadata": { "category": "desk" }
+        }]"#;
+        let mut harness = Harness::default();
+        harness.controller.set_catalog(Catalog::from_str(catalog).unwrap());
+        harness.send(Message::CatalogButton(0));
+
+        harness.click((0, 0));
+        // Overlaps the first desk
+        harness.click((4, 2));
+        harness.click((0, 10));
+
+        let plan = harness.controller.plan();
+        assert_eq!(plan.iter().count(), 2);
+        for pos in [(0, 0), (0, 10)] {
+            let shape = Shape::from(Rectangle::new(pos.into(), 8, 4));
+            let (id, _) = plan.iter().find(|(_, s)| *s == shape).unwrap();
+            let metadata = plan.get_metadata(*id).unwrap();
+            assert_eq!(metadata.name.as_deref(), Some("Desk"));
+            assert_eq!(metadata.category.as_deref(), Some("desk"));
+        }
+        let history: Vec<_> = harness
+            .controller
+            .history()
+            .into_iter()
+            .map(|entry| entry.description)
+            .collect();
+        assert_eq!(history, ["Add Desk", "Add Desk"]);
+
+        harness.send(Message::Cancel);
+        assert!(harness.controller.idle());
+    }
+}
@@ -0,0 +1,99 @@
+use iced::event::Status;
+use iced::widget::canvas::{Event, Frame, Program};
+use iced::mouse;
+
+use crate::controller::Controller;
+use crate::message::Message;
+use crate::model::plan::BlockId;
+use crate::view;
+use crate::view::plan::screen_to_world;
+
+/// Place instances of a block, with the origin of the block under the cursor
+#[derive(Debug)]
+pub struct PlaceBlock<'a> {
+    controller: &'a Controller,
+    block_id: BlockId,
+}
+
+impl<'a> PlaceBlock<'a> {
+    pub fn new(controller: &'a Controller, block_id: BlockId) -> Self {
+        Self {
+            controller,
+            block_id,
+        }
+    }
+}
+
+impl Program<Message> for PlaceBlock<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer<iced::Theme>,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> Vec<<iced::Renderer<iced::Theme> as iced::widget::canvas::Renderer>::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let plan = self.controller.plan();
+        let scale = self.controller.scale();
+
+        view::plan::draw_room(&mut frame, plan, scale);
+        view::plan::draw_shapes(&mut frame, plan.iter(), scale);
+        view::plan::draw_dimensions(&mut frame, plan, scale, &[]);
+
+        let block = plan.get_block(self.block_id);
+        if let (Some(block), Some(screen_cursor_pos)) = (block, cursor.position_in(bounds)) {
+            let world_cursor_pos = screen_to_world(screen_cursor_pos, scale);
+            for shape in block.shapes_at(world_cursor_pos) {
+                view::plan::draw_preview(&mut frame, plan, &shape, &[], scale);
+            }
+            view::plan::draw_label(
+                &mut frame,
+                screen_cursor_pos,
+                &[
+                    block.name().into(),
+                    view::plan::format_position(world_cursor_pos),
+                ],
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: Event,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> (Status, Option<Message>) {
+        let cursor_message = view::plan::track_cursor(self.controller, &event, bounds);
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let (Some(screen_cursor_pos), Some(block)) = (
+                    cursor.position_in(bounds),
+                    self.controller.plan().get_block(self.block_id),
+                ) else {
+                    return (Status::Ignored, None);
+                };
+
+                let world_cursor_pos = screen_to_world(screen_cursor_pos, self.controller.scale());
+                let shapes = block.shapes_at(world_cursor_pos);
+                let message = self
+                    .controller
+                    .plan()
+                    .are_disjoint(&shapes, &[])
+                    .then_some(Message::AddInstance(self.block_id, world_cursor_pos));
+
+                (Status::Captured, message)
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                (Status::Captured, Some(Message::Cancel))
+            }
+            _ => (Status::Ignored, cursor_message),
+        }
+    }
+}
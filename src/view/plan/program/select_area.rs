@@ -0,0 +1,235 @@
+use std::mem;
+
+use iced::event::Status;
+use iced::widget::canvas::{Event, Frame, Path, Program, Stroke};
+use iced::{mouse, Color, Point, Size};
+
+use crate::controller::state::AreaAction;
+use crate::controller::Controller;
+use crate::message::Message;
+use crate::model::vec2::Vec2;
+use crate::view;
+use crate::view::plan::{screen_to_world, world_to_screen};
+
+const SELECTION_COLOR: Color = Color::from_rgb(0.1, 0.3, 0.9);
+
+#[derive(Debug, Default)]
+pub enum State {
+    #[default]
+    Waiting,
+    Selecting {
+        start: Vec2,
+        end: Vec2,
+    },
+}
+
+impl State {
+    /// The controller cancels the gesture when the history is used while selecting,
+    /// the state is then stale and must be handled as `Waiting`
+    fn is_cancelled(&self, controller: &Controller) -> bool {
+        matches!(self, State::Selecting { .. }) && !controller.gesture_in_progress()
+    }
+}
+
+/// Select an area, its shapes are used by the `action`
+#[derive(Debug)]
+pub struct SelectArea<'a> {
+    controller: &'a Controller,
+    action: AreaAction,
+}
+
+impl<'a> SelectArea<'a> {
+    pub fn new(controller: &'a Controller, action: AreaAction) -> Self {
+        Self { controller, action }
+    }
+}
+
+impl Program<Message> for SelectArea<'_> {
+    type State = State;
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &iced::Renderer<iced::Theme>,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        _cursor: iced::advanced::mouse::Cursor,
+    ) -> Vec<<iced::Renderer<iced::Theme> as iced::widget::canvas::Renderer>::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let plan = self.controller.plan();
+        let scale = self.controller.scale();
+
+        view::plan::draw_room(&mut frame, plan, scale);
+        view::plan::draw_shapes(&mut frame, plan.iter(), scale);
+        view::plan::draw_dimensions(&mut frame, plan, scale, &[]);
+
+        if let (State::Selecting { start, end }, false) = (state, state.is_cancelled(self.controller)) {
+            let selection = self.controller.area_selection(self.action, *start, *end);
+            for shape in selection.into_iter().filter_map(|id| plan.get_shape(id)) {
+                let path = view::plan::draw_shape(shape, scale);
+                frame.stroke(
+                    &path,
+                    Stroke::default().with_color(SELECTION_COLOR).with_width(3.0),
+                );
+            }
+
+            let (start, end) = (world_to_screen(*start, scale), world_to_screen(*end, scale));
+            let top_left = Point::new(start.x.min(end.x), start.y.min(end.y));
+            let size = Size::new((end.x - start.x).abs(), (end.y - start.y).abs());
+            let area = Path::rectangle(top_left, size);
+            frame.fill(&area, Color { a: 0.1, ..SELECTION_COLOR });
+            frame.stroke(&area, Stroke::default().with_color(SELECTION_COLOR));
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: Event,
+        bounds: iced::Rectangle,
+        cursor: iced::advanced::mouse::Cursor,
+    ) -> (Status, Option<Message>) {
+        let cursor_message = view::plan::track_cursor(self.controller, &event, bounds);
+
+        if state.is_cancelled(self.controller) {
+            *state = State::Waiting;
+        }
+
+        match (&mut *state, event) {
+            (State::Waiting, Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))) => {
+                let Some(screen_cursor_pos) = cursor.position_in(bounds) else {
+                    return (Status::Ignored, None);
+                };
+
+                let world_cursor_pos = screen_to_world(screen_cursor_pos, self.controller.scale());
+                *state = State::Selecting {
+                    start: world_cursor_pos,
+                    end: world_cursor_pos,
+                };
+
+                (Status::Captured, Some(Message::GestureStarted))
+            }
+            (
+                State::Selecting { ref mut end, .. },
+                Event::Mouse(mouse::Event::CursorMoved {
+                    position: absolute_pos,
+                }),
+            ) => {
+                if bounds.contains(absolute_pos) {
+                    let relative_pos = Point::ORIGIN + (absolute_pos - bounds.position());
+                    *end = screen_to_world(relative_pos, self.controller.scale());
+
+                    (Status::Captured, cursor_message)
+                } else {
+                    (Status::Ignored, cursor_message)
+                }
+            }
+            (
+                State::Selecting { .. },
+                Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)),
+            ) => {
+                let State::Selecting { start, end } = mem::replace(state, State::Waiting) else {
+                    unreachable!()
+                };
+
+                (Status::Captured, Some(Message::AreaSelected(start, end)))
+            }
+            (_, Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right))) => {
+                (Status::Captured, Some(Message::Cancel))
+            }
+            _ => (Status::Ignored, cursor_message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::message::Message;
+    use crate::model::catalog::Catalog;
+    use crate::model::shape::{Circle, Rectangle};
+    use crate::view::plan::program::harness::Harness;
+
+    #[test]
+    fn group_and_ungroup() {
+        let mut harness = Harness::default();
+        harness.send(Message::AddShape(Rectangle::new((0, 0).into(), 4, 2).into()));
+        harness.send(Message::AddShape(Circle::new((2, 5).into(), 1).into()));
+        harness.send(Message::AddShape(Circle::new((20, 20).into(), 1).into()));
+        harness.send(Message::GroupButton);
+
+        harness.drag((0, 0), (6, 6));
+
+        let plan = harness.controller.plan();
+        let ids: Vec<_> = plan.iter().map(|(id, _)| *id).collect();
+        assert_eq!(plan.group_of(ids[0]).len(), 2);
+        assert_eq!(plan.group_of(ids[2]), [ids[2]]);
+
+        // The whole group is ungrouped even if only a member is in the area
+        harness.send(Message::UngroupButton);
+        harness.drag((1, 4), (3, 6));
+        let plan = harness.controller.plan();
+        assert!(ids.iter().all(|id| plan.get_group(*id).is_none()));
+
+        harness.send(Message::Undo);
+        assert_eq!(harness.controller.plan().group_of(ids[1]).len(), 2);
+    }
+
+    #[test]
+    fn create_and_place_block() {
+        let mut harness = Harness::default();
+        harness.send(Message::AddShape(Rectangle::new((2, 2).into(), 4, 2).into()));
+        harness.send(Message::AddShape(Circle::new((4, 6).into(), 1).into()));
+        harness.send(Message::AddShape(Circle::new((20, 20).into(), 1).into()));
+        harness.send(Message::CreateBlockButton);
+
+        harness.drag((1, 1), (8, 8));
+
+        let plan = harness.controller.plan();
+        let (block_id, block) = plan.blocks().next().unwrap().clone();
+        assert_eq!(block.shapes().len(), 2);
+        assert_eq!(plan.block_members(block_id).count(), 2);
+        assert!(harness.controller.idle());
+
+        harness.send(Message::BlockButton(block_id));
+        // Overlaps the first instance
+        harness.click((3, 3));
+        harness.click((10, 2));
+
+        let plan = harness.controller.plan();
+        assert_eq!(plan.iter().count(), 5);
+        assert_eq!(plan.block_members(block_id).count(), 4);
+        let circle = Circle::new((12, 6).into(), 1).into();
+        assert!(plan.iter().any(|(_, shape)| *shape == circle));
+    }
+
+    #[test]
+    fn set_room_and_pack_catalog_items() {
+        let catalog = r#"[
+            { "name": "Desk", "shape": { "Rectangle": { "width": 8, "height": 4 } } },
+            { "name": "Cabinet", "shape": { "Rectangle": { "width": 2, "height": 2 } } }
+        ]"#;
+        let mut harness = Harness::default();
+        harness.controller.set_catalog(Catalog::from_str(catalog).unwrap());
+        harness.send(Message::SetRoomButton);
+        harness.drag((20, 10), (0, 0));
+        assert!(harness.controller.plan().room().is_some());
+
+        harness.send(Message::PackQuantityChanged(0, "5".into()));
+        harness.send(Message::PackQuantityChanged(1, "3".into()));
+        harness.send(Message::PackButton);
+
+        assert_eq!(harness.controller.notice(), Some("7 of 8 items fit in the room"));
+        let plan = harness.controller.plan();
+        let desks = plan
+            .iter()
+            .filter(|(id, _)| plan.get_metadata(*id).unwrap().name.as_deref() == Some("Desk"));
+        assert_eq!(desks.count(), 4);
+
+        harness.send(Message::Undo);
+        assert_eq!(harness.controller.plan().iter().count(), 0);
+        harness.send(Message::Undo);
+        assert!(harness.controller.plan().room().is_none());
+    }
+}